@@ -1,280 +1,351 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion, Fun};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 use bittyset::BitSet as BittySet;
 use bit_set::BitSet;
 use rand::Rng;
 
 fn random_vec(len: usize, nbits: usize) -> Vec<usize> {
   let mut rng = rand::thread_rng();
-  (0..len).map(|_| rng.gen_range(0usize, 1 << nbits)).collect()
+  (0..len).map(|_| rng.gen_range(0..1usize << nbits)).collect()
 }
 
 fn insert_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "insert",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        let mut set = BitSet::new();
-        b.iter_with_large_setup(|| random_vec(10000, 12), |input| {
-          set.clear();
-          
-          for i in input {
-            set.insert(i);
-          }
-        });
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        let mut set = BittySet::<u32>::new();
-        b.iter_with_large_setup(|| random_vec(10000, 12), |input| {
-          set.clear();
-          
-          for i in input {
-            set.insert(i);
-          }
-        });
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("insert");
+
+  group.bench_function("bit_set", |b| {
+    let mut set = BitSet::new();
+    b.iter_batched(|| random_vec(10000, 12), |input| {
+      set.clear();
+      
+      for i in input {
+        set.insert(i);
+      }
+    }, BatchSize::LargeInput);
+  });
+
+  group.bench_function("bittyset", |b| {
+    let mut set = BittySet::<u32>::new();
+    b.iter_batched(|| random_vec(10000, 12), |input| {
+      set.clear();
+      
+      for i in input {
+        set.insert(i);
+      }
+    }, BatchSize::LargeInput);
+  });
+
+  group.finish();
 }
 
 fn remove_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "remove",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 12).into_iter().collect::<BitSet>(),
-               random_vec(5000, 12)
-             ),
-          |(mut set, remove)| {
-            for i in remove {
-              set.remove(i);
-            }
-          }
-        );
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 12).into_iter().collect::<BittySet<u32>>(),
-               random_vec(5000, 12)
-             ),
-          |(mut set, remove)| {
-            for i in remove {
-              set.remove(i);
-            }
-          }
-        );
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("remove");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 12).into_iter().collect::<BitSet>(),
+           random_vec(5000, 12)
+         ),
+      |(mut set, remove)| {
+        for i in remove {
+          set.remove(i);
+        }
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 12).into_iter().collect::<BittySet<u32>>(),
+           random_vec(5000, 12)
+         ),
+      |(mut set, remove)| {
+        for i in remove {
+          set.remove(i);
+        }
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
+}
+
+fn remove_descending_benchmark(c: &mut Criterion) {
+  let mut group = c.benchmark_group("remove_descending");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || (0..100000).collect::<BitSet>(),
+      |mut set| {
+        for i in (0..100000).rev() {
+          set.remove(i);
+        }
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || (0..100000).collect::<BittySet<u32>>(),
+      |mut set| {
+        for i in (0..100000).rev() {
+          set.remove(i);
+        }
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
 }
 
 fn contains_benchmark(c: &mut Criterion) {
   let insert = random_vec(10000, 12);
   let contains = random_vec(5000, 12);
 
-  c.bench_functions(
-    "contains",
-    vec![
-      Fun::new("bit_set", |b, (insert, contains): &(Vec<usize>, _)| {
-        let set = insert.iter().cloned().collect::<BitSet>();
-        b.iter(|| {
-          for &i in contains {
-            black_box(set.contains(i));
-          }
-        });
-      }),
-
-      Fun::new("bittyset", |b, (insert, contains): &(Vec<usize>, _)| {
-        let set = insert.iter().cloned().collect::<BittySet<u32>>();
-        b.iter(|| {
-          for &i in contains {
-            black_box(set.contains(i));
-          }
-        });
-      })
-    ],
-    (insert, contains));
+  let mut group = c.benchmark_group("contains");
+
+  group.bench_function("bit_set", |b| {
+    let set = insert.iter().cloned().collect::<BitSet>();
+    b.iter(|| {
+      for &i in &contains {
+        black_box(set.contains(i));
+      }
+    });
+  });
+
+  group.bench_function("bittyset", |b| {
+    let set = insert.iter().cloned().collect::<BittySet<u32>>();
+    b.iter(|| {
+      for &i in &contains {
+        black_box(set.contains(i));
+      }
+    });
+  });
+
+  group.finish();
 }
 
 fn union_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "union",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
-               random_vec(10000, 16).into_iter().collect::<BitSet>()
-             ),
-          |(mut set1, set2)| {
-            set1.union_with(&set2);
-          }
-        );
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
-               random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
-             ),
-          |(mut set1, set2)| {
-            set1.union_with(&set2);
-          }
-        );
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("union");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
+           random_vec(10000, 16).into_iter().collect::<BitSet>()
+         ),
+      |(mut set1, set2)| {
+        set1.union_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
+           random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
+         ),
+      |(mut set1, set2)| {
+        set1.union_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
 }
 
 fn intersection_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "intersection",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
-               random_vec(10000, 16).into_iter().collect::<BitSet>()
-             ),
-          |(mut set1, set2)| {
-            set1.intersect_with(&set2);
-          }
-        );
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
-               random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
-             ),
-          |(mut set1, set2)| {
-            set1.intersect_with(&set2);
-          }
-        );
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("intersection");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
+           random_vec(10000, 16).into_iter().collect::<BitSet>()
+         ),
+      |(mut set1, set2)| {
+        set1.intersect_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
+           random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
+         ),
+      |(mut set1, set2)| {
+        set1.intersect_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
+}
+
+fn repeated_intersection_benchmark(c: &mut Criterion) {
+  let mut group = c.benchmark_group("repeated_intersection");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || ( (0..1usize << 16).collect::<BitSet>(),
+           (0..32).map(|_| random_vec(30000, 16).into_iter().collect::<BitSet>()).collect::<Vec<_>>()
+         ),
+      |(mut set, others)| {
+        for other in &others {
+          set.intersect_with(other);
+        }
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || ( (0..1usize << 16).collect::<BittySet<u32>>(),
+           (0..32).map(|_| random_vec(30000, 16).into_iter().collect::<BittySet<u32>>()).collect::<Vec<_>>()
+         ),
+      |(mut set, others)| {
+        for other in &others {
+          set.intersect_with(other);
+        }
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
 }
 
 fn difference_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "difference",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
-               random_vec(10000, 16).into_iter().collect::<BitSet>()
-             ),
-          |(mut set1, set2)| {
-            set1.difference_with(&set2);
-          }
-        );
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
-               random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
-             ),
-          |(mut set1, set2)| {
-            set1.difference_with(&set2);
-          }
-        );
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("difference");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
+           random_vec(10000, 16).into_iter().collect::<BitSet>()
+         ),
+      |(mut set1, set2)| {
+        set1.difference_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
+           random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
+         ),
+      |(mut set1, set2)| {
+        set1.difference_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
 }
 
 fn symmetric_difference_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "symmetric_difference",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
-               random_vec(10000, 16).into_iter().collect::<BitSet>()
-             ),
-          |(mut set1, set2)| {
-            set1.symmetric_difference_with(&set2);
-          }
-        );
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
-               random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
-             ),
-          |(mut set1, set2)| {
-            set1.symmetric_difference_with(&set2);
-          }
-        );
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("symmetric_difference");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BitSet>(),
+           random_vec(10000, 16).into_iter().collect::<BitSet>()
+         ),
+      |(mut set1, set2)| {
+        set1.symmetric_difference_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || ( random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
+           random_vec(10000, 16).into_iter().collect::<BittySet<u32>>()
+         ),
+      |(mut set1, set2)| {
+        set1.symmetric_difference_with(&set2);
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
 }
 
 /// Benchmark reflexivity of subset relation.
 fn is_subset_refl_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "is_subset(self)",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        b.iter_with_large_setup(
-          || random_vec(10000, 16).into_iter().collect::<BitSet>(),
-          |set| {
-            black_box(set.is_subset(&set));
-          }
-        );
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        b.iter_with_large_setup(
-          || random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
-          |set| {
-            black_box(set.is_subset(&set));
-          }
-        );
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("is_subset(self)");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || random_vec(10000, 16).into_iter().collect::<BitSet>(),
+      |set| {
+        black_box(set.is_subset(&set));
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || random_vec(10000, 16).into_iter().collect::<BittySet<u32>>(),
+      |set| {
+        black_box(set.is_subset(&set));
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
 }
 
 fn is_subset_benchmark(c: &mut Criterion) {
-  c.bench_functions(
-    "is_subset",
-    vec![
-      Fun::new("bit_set", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(100, 12).into_iter().collect::<BitSet>(),
-               random_vec(10000, 12).into_iter().collect::<BitSet>()
-             ),
-          |(set1, set2)| {
-            black_box(set1.is_subset(&set2));
-          }
-        );
-      }),
-
-      Fun::new("bittyset", |b, _| {
-        b.iter_with_large_setup(
-          || ( random_vec(100, 12).into_iter().collect::<BittySet<u32>>(),
-               random_vec(10000, 12).into_iter().collect::<BittySet<u32>>()
-             ),
-          |(set1, set2)| {
-            black_box(set1.is_subset(&set2));
-          }
-        );
-      })
-    ],
-    ());
+  let mut group = c.benchmark_group("is_subset");
+
+  group.bench_function("bit_set", |b| {
+    b.iter_batched(
+      || ( random_vec(100, 12).into_iter().collect::<BitSet>(),
+           random_vec(10000, 12).into_iter().collect::<BitSet>()
+         ),
+      |(set1, set2)| {
+        black_box(set1.is_subset(&set2));
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.bench_function("bittyset", |b| {
+    b.iter_batched(
+      || ( random_vec(100, 12).into_iter().collect::<BittySet<u32>>(),
+           random_vec(10000, 12).into_iter().collect::<BittySet<u32>>()
+         ),
+      |(set1, set2)| {
+        black_box(set1.is_subset(&set2));
+      },
+      BatchSize::LargeInput
+    );
+  });
+
+  group.finish();
 }
 
 criterion_group!(benches,
   insert_benchmark,
   remove_benchmark,
+  remove_descending_benchmark,
   contains_benchmark,
   union_benchmark,
   intersection_benchmark,
+  repeated_intersection_benchmark,
   difference_benchmark,
   symmetric_difference_benchmark,
   is_subset_benchmark,
@@ -0,0 +1,128 @@
+//! This crate provides the derive macro of the `BitSetKey` trait of
+//! [bittyset](https://docs.rs/bittyset). Use it through the `derive` feature
+//! of `bittyset` rather than directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields};
+
+/// The maximum number of variants, limited by the largest array block
+/// `[u64; 32]`.
+const MAX_VARIANTS: usize = 64 * 32;
+
+/// Derives `BitSetKey` for a fieldless enum, mapping the variants to bit
+/// indices in declaration order.
+#[proc_macro_derive(BitSetKey)]
+pub fn derive_bit_set_key(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand(input)
+    .unwrap_or_else(Error::into_compile_error)
+    .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+  let data = match &input.data {
+    Data::Enum(data) => data,
+    _ => return Err(Error::new_spanned(&input.ident, "BitSetKey can only be derived for enums")),
+  };
+  if !input.generics.params.is_empty() {
+    return Err(Error::new_spanned(&input.generics, "BitSetKey can not be derived for generic enums"));
+  }
+  if let Some(variant) = data.variants.iter().find(|v| !matches!(v.fields, Fields::Unit)) {
+    return Err(Error::new_spanned(variant, "BitSetKey can only be derived for fieldless enums"));
+  }
+  if data.variants.len() > MAX_VARIANTS {
+    return Err(Error::new_spanned(
+      &input.ident,
+      format!("BitSetKey can not be derived for enums of more than {} variants", MAX_VARIANTS),
+    ));
+  }
+
+  let name = &input.ident;
+  let count = data.variants.len();
+  let variants = data.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+  let indices = (0..count).collect::<Vec<_>>();
+
+  let (block, empty, all, set_bit) = match count {
+    0..=128 => {
+      let ty = match count {
+        0..=8 => quote!(u8),
+        9..=16 => quote!(u16),
+        17..=32 => quote!(u32),
+        33..=64 => quote!(u64),
+        _ => quote!(u128),
+      };
+      let all = if count == 0 {
+        quote!(0)
+      } else {
+        quote!(#ty::MAX >> (#ty::BITS as usize - #count))
+      };
+      (ty.clone(), quote!(0), all, quote!(blk |= (1 as #ty) << index;))
+    },
+    _ => {
+      let len = count.div_ceil(64);
+      let words = (0..len).map(|i| {
+        let nbits = (count - i * 64).min(64);
+        quote!(u64::MAX >> (64 - #nbits))
+      });
+      (
+        quote!([u64; #len]),
+        quote!([0; #len]),
+        quote!([#(#words),*]),
+        quote!(blk[index / 64] |= 1 << (index % 64);),
+      )
+    },
+  };
+
+  // The helper of `enum_set!` is defined in an anonymous constant, so that
+  // it is only reachable through `BitSetKey::ConstBlock`.
+  Ok(quote! {
+    const _: () = {
+      pub struct ConstBlock;
+
+      impl ConstBlock {
+        #[allow(unused_mut, unused_variables, unreachable_code)]
+        pub const fn block(keys: &[#name]) -> #block {
+          let mut blk = #empty;
+          let mut i = 0;
+          while i < keys.len() {
+            let index: usize = match keys[i] {
+              #(#name::#variants => #indices,)*
+            };
+            #set_bit
+            i += 1;
+          }
+          blk
+        }
+      }
+
+      impl ::bittyset::BitSetKey for #name {
+        type Block = #block;
+
+        type ConstBlock = ConstBlock;
+
+        const COUNT: usize = #count;
+
+        const EMPTY: #block = #empty;
+
+        const ALL: #block = #all;
+
+        #[inline]
+        fn to_index(self) -> usize {
+          match self {
+            #(Self::#variants => #indices,)*
+          }
+        }
+
+        #[inline]
+        fn from_index(index: usize) -> Self {
+          match index {
+            #(#indices => Self::#variants,)*
+            _ => panic!("index out of range"),
+          }
+        }
+      }
+    };
+  })
+}
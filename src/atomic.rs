@@ -0,0 +1,292 @@
+use std::fmt::{self, Debug, Formatter};
+use std::sync::atomic::{AtomicU8, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use crate::{BitBlock, BitSet};
+
+/// A trait for `BitBlock`s which have a corresponding atomic type, used by
+/// `AtomicBitSet`.
+pub trait AtomicBitBlock: BitBlock {
+  #[doc(hidden)]
+  type Atomic: Send + Sync;
+
+  #[doc(hidden)]
+  fn new_atomic(value: Self) -> Self::Atomic;
+
+  #[doc(hidden)]
+  fn load(atomic: &Self::Atomic, order: Ordering) -> Self;
+
+  #[doc(hidden)]
+  fn store(atomic: &Self::Atomic, value: Self, order: Ordering);
+
+  #[doc(hidden)]
+  fn fetch_or(atomic: &Self::Atomic, value: Self, order: Ordering) -> Self;
+
+  #[doc(hidden)]
+  fn fetch_and(atomic: &Self::Atomic, value: Self, order: Ordering) -> Self;
+}
+
+macro_rules! impl_atomic_bit_block {
+  ($type:ty, $atomic:ty) => {
+    impl AtomicBitBlock for $type {
+      type Atomic = $atomic;
+
+      fn new_atomic(value: Self) -> Self::Atomic {
+        <$atomic>::new(value)
+      }
+
+      fn load(atomic: &Self::Atomic, order: Ordering) -> Self {
+        atomic.load(order)
+      }
+
+      fn store(atomic: &Self::Atomic, value: Self, order: Ordering) {
+        atomic.store(value, order)
+      }
+
+      fn fetch_or(atomic: &Self::Atomic, value: Self, order: Ordering) -> Self {
+        atomic.fetch_or(value, order)
+      }
+
+      fn fetch_and(atomic: &Self::Atomic, value: Self, order: Ordering) -> Self {
+        atomic.fetch_and(value, order)
+      }
+    }
+  }
+}
+
+impl_atomic_bit_block!(u8, AtomicU8);
+impl_atomic_bit_block!(u16, AtomicU16);
+impl_atomic_bit_block!(u32, AtomicU32);
+impl_atomic_bit_block!(u64, AtomicU64);
+impl_atomic_bit_block!(usize, AtomicUsize);
+
+/// A fixed-capacity bit set which can be modified concurrently.
+///
+/// Elements are stored in atomic blocks in the same layout as `BitSet<T>`.
+/// `insert` and `remove` take `&self` and use `fetch_or` and `fetch_and` on
+/// the block holding the value, so the set can be shared between threads.
+///
+/// Methods without an explicit `Ordering` use `AcqRel` for modifications and
+/// `Acquire` for loads.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::AtomicBitSet;
+///
+/// let set = AtomicBitSet::<u64>::with_capacity(1000);
+///
+/// std::thread::scope(|s| {
+///   for t in 0..4 {
+///     let set = &set;
+///     s.spawn(move || {
+///       for i in (t..1000).step_by(4) {
+///         set.insert(i);
+///       }
+///     });
+///   }
+/// });
+///
+/// assert_eq!(set.len(), 1000);
+/// ```
+pub struct AtomicBitSet<T: AtomicBitBlock = usize> {
+  vec: Vec<T::Atomic>,
+}
+
+impl<T> AtomicBitSet<T>
+where
+  T: AtomicBitBlock,
+{
+  /// Creates a new empty `AtomicBitSet` which can hold values less than
+  /// `capacity`.
+  ///
+  /// Note that the actual capacity of the created set may be greater than the
+  /// given `capacity`, to make best use of the space of the underlying bit
+  /// vector.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::AtomicBitSet;
+  ///
+  /// let set = AtomicBitSet::<u8>::with_capacity(5);
+  /// assert_eq!(set.capacity(), 8);
+  /// ```
+  pub fn with_capacity(capacity: usize) -> Self {
+    let nblks = crate::compute_num_blocks::<T>(capacity);
+    Self {
+      vec: (0..nblks).map(|_| T::new_atomic(T::zero())).collect(),
+    }
+  }
+
+  /// Creates an `AtomicBitSet` with the elements of `set`, which can hold
+  /// values less than `capacity` or present in `set`.
+  pub fn from_bitset(set: &BitSet<T>, capacity: usize) -> Self {
+    let blocks = set.as_blocks();
+    let nblks = crate::compute_num_blocks::<T>(capacity).max(blocks.len());
+    Self {
+      vec: (0..nblks)
+        .map(|i| T::new_atomic(blocks.get(i).copied().unwrap_or_else(T::zero)))
+        .collect(),
+    }
+  }
+
+  /// Returns the capacity of the set. Only values less than the capacity can
+  /// be inserted.
+  pub fn capacity(&self) -> usize {
+    self.vec.len() * T::NUM_BITS
+  }
+
+  #[inline(always)]
+  fn locate(&self, value: usize) -> (&T::Atomic, T) {
+    let atomic = self.vec.get(value / T::NUM_BITS)
+      .expect("value out of capacity of AtomicBitSet");
+    (atomic, T::one().shl_bits(value % T::NUM_BITS))
+  }
+
+  /// Adds a value to the set. Returns whether the value was not present in
+  /// the set.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `value` is not less than the capacity.
+  pub fn insert(&self, value: usize) -> bool {
+    self.insert_ordered(value, Ordering::AcqRel)
+  }
+
+  /// Adds a value to the set with the given memory ordering. Returns whether
+  /// the value was not present in the set.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `value` is not less than the capacity.
+  pub fn insert_ordered(&self, value: usize, order: Ordering) -> bool {
+    let (atomic, mask) = self.locate(value);
+    T::fetch_or(atomic, mask, order).bit_and(mask) == T::zero()
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&self, value: usize) -> bool {
+    self.remove_ordered(value, Ordering::AcqRel)
+  }
+
+  /// Removes a value from the set with the given memory ordering. Returns
+  /// whether the value was present in the set.
+  pub fn remove_ordered(&self, value: usize, order: Ordering) -> bool {
+    if value >= self.capacity() {
+      return false;
+    }
+
+    let (atomic, mask) = self.locate(value);
+    T::fetch_and(atomic, mask.bit_not(), order).bit_and(mask) != T::zero()
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.contains_ordered(value, Ordering::Acquire)
+  }
+
+  /// Returns whether the given `value` is present in the set, loading with
+  /// the given memory ordering. `Release` and `AcqRel`, which are invalid for
+  /// loads, are taken as their load side, `Relaxed` and `Acquire`.
+  pub fn contains_ordered(&self, value: usize, order: Ordering) -> bool {
+    if value >= self.capacity() {
+      return false;
+    }
+
+    let order = match order {
+      Ordering::Release => Ordering::Relaxed,
+      Ordering::AcqRel => Ordering::Acquire,
+      order => order,
+    };
+    let (atomic, mask) = self.locate(value);
+    T::load(atomic, order).bit_and(mask) != T::zero()
+  }
+
+  /// Returns the number of elements in the set.
+  ///
+  /// The blocks are loaded one by one, so the result may not correspond to
+  /// any single state of the set if it is modified concurrently.
+  pub fn len(&self) -> usize {
+    self.vec.iter()
+      .map(|x| T::load(x, Ordering::Acquire).count_ones() as usize)
+      .sum()
+  }
+
+  /// Returns whether the set is empty.
+  ///
+  /// See [`len`](#method.len) for consistency under concurrent modifications.
+  pub fn is_empty(&self) -> bool {
+    self.vec.iter().all(|x| T::load(x, Ordering::Acquire) == T::zero())
+  }
+
+  /// Clear the set, removing all elements.
+  pub fn clear(&self) {
+    for x in &self.vec {
+      T::store(x, T::zero(), Ordering::Release);
+    }
+  }
+
+  /// Iterates over the set, producing `usize`s representing the elements in
+  /// the set, in ascending order.
+  ///
+  /// The iterator is weakly consistent: each block is loaded when the
+  /// iteration reaches it, so concurrent modifications of blocks not yet
+  /// reached are observed, and modifications of blocks already passed are not.
+  pub fn iter(&self) -> AtomicIter<'_, T> {
+    AtomicIter {
+      set: self,
+      index: 0,
+      blk: T::zero(),
+    }
+  }
+
+  /// Copies the set into a `BitSet`.
+  ///
+  /// The snapshot is weakly consistent in the same way as [`iter`].
+  ///
+  /// [`iter`]: #method.iter
+  pub fn to_bitset(&self) -> BitSet<T> {
+    BitSet::from_vec(self.vec.iter().map(|x| T::load(x, Ordering::Acquire)).collect())
+  }
+}
+
+impl<T> Debug for AtomicBitSet<T>
+where
+  T: AtomicBitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_set().entries(self.iter()).finish()
+  }
+}
+
+/// An iterator for `AtomicBitSet`.
+///
+/// This struct is created by the [`iter`] method on [`AtomicBitSet`]s.
+///
+/// [`AtomicBitSet`]: struct.AtomicBitSet.html
+/// [`iter`]: struct.AtomicBitSet.html#method.iter
+pub struct AtomicIter<'a, T: AtomicBitBlock> {
+  set: &'a AtomicBitSet<T>,
+  /// Index of the next block to load.
+  index: usize,
+  /// The remaining bits of the last loaded block.
+  blk: T,
+}
+
+impl<'a, T> Iterator for AtomicIter<'a, T>
+where
+  T: AtomicBitBlock,
+{
+  type Item = usize;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while self.blk == T::zero() {
+      self.blk = T::load(self.set.vec.get(self.index)?, Ordering::Acquire);
+      self.index += 1;
+    }
+
+    let bit = self.blk.trailing_zeros() as usize;
+    self.blk = self.blk.bit_xor(T::one().shl_bits(bit));
+    Some((self.index - 1) * T::NUM_BITS + bit)
+  }
+}
@@ -1,20 +1,204 @@
-use std::ops::{BitOrAssign, BitAndAssign, BitXorAssign};
+use std::fmt::Debug;
 use std::hash::Hash;
-use num_traits::int::PrimInt;
+use std::ops::{Deref, DerefMut};
+use crate::BitSet;
 
 /// A trait for representing elements of the underlying bit vector of `BitSet`.
-pub trait BitBlock: Default + Eq + Hash + PrimInt +
-  BitOrAssign + BitAndAssign + BitXorAssign
-{
+///
+/// Only the operations below are required, so besides the unsigned integer
+/// types, fixed-size arrays of blocks such as `[u64; 4]` are also blocks. The
+/// bit indexed by `i` of an array block is the bit `i % T::NUM_BITS` of the
+/// element `i / T::NUM_BITS`.
+///
+/// The bit indices passed to `shl_bits` and `shr_bits` are always less than
+/// `NUM_BITS`.
+///
+/// The operations are named apart from the methods of `std::ops`, so that
+/// they do not shadow the operators on integer blocks in generic code which
+/// also bounds `T` by the operator traits. This replaces the `PrimInt` and
+/// `BitOrAssign`/`BitAndAssign`/`BitXorAssign` supertraits of earlier
+/// versions: generic code relying on them should use these methods, or add
+/// the bounds it needs alongside `BitBlock`.
+pub trait BitBlock: Copy + Default + Eq + Hash + Debug {
   #[doc(hidden)]
   const NUM_BITS: usize;
+
+  /// Returns the block with all bits unset.
+  fn zero() -> Self;
+
+  /// Returns the block with only the lowest bit set.
+  fn one() -> Self;
+
+  /// Returns the bitwise AND of two blocks.
+  fn bit_and(self, other: Self) -> Self;
+
+  /// Returns the bitwise OR of two blocks.
+  fn bit_or(self, other: Self) -> Self;
+
+  /// Returns the bitwise XOR of two blocks.
+  fn bit_xor(self, other: Self) -> Self;
+
+  /// Returns the bitwise complement of the block.
+  fn bit_not(self) -> Self;
+
+  /// Returns the number of set bits.
+  fn count_ones(self) -> u32;
+
+  /// Returns the number of unset bits below the lowest set bit, or `NUM_BITS`
+  /// if no bit is set.
+  fn trailing_zeros(self) -> u32;
+
+  /// Returns the number of unset bits above the highest set bit, or
+  /// `NUM_BITS` if no bit is set.
+  fn leading_zeros(self) -> u32;
+
+  /// Shifts the bits towards the higher indices by `n`.
+  fn shl_bits(self, n: usize) -> Self;
+
+  /// Shifts the bits towards the lower indices by `n`.
+  fn shr_bits(self, n: usize) -> Self;
+
+  /// Returns the lowest `usize::BITS` bits of the block as a `usize`.
+  #[doc(hidden)]
+  fn to_usize(self) -> usize {
+    let mut blk = self;
+    let mut x = 0;
+    while blk != Self::zero() {
+      let i = blk.trailing_zeros() as usize;
+      if i >= usize::BITS as usize {
+        break;
+      }
+      x |= 1 << i;
+      blk = blk.bit_xor(Self::one().shl_bits(i));
+    }
+    x
+  }
+
+  /// Returns the block with the bits of `x`, truncated to `NUM_BITS`.
+  #[doc(hidden)]
+  fn from_usize(mut x: usize) -> Self {
+    let mut blk = Self::zero();
+    while x != 0 {
+      let i = x.trailing_zeros() as usize;
+      if i >= Self::NUM_BITS {
+        break;
+      }
+      blk = blk.bit_or(Self::one().shl_bits(i));
+      x &= x - 1;
+    }
+    blk
+  }
+
+  /// Reinterprets `slice` as bytes, if the blocks are plain integers. This
+  /// enables the SIMD kernels for the set operations.
+  #[doc(hidden)]
+  fn as_bytes(_slice: &[Self]) -> Option<&[u8]> {
+    None
+  }
+
+  /// Mutable version of `as_bytes`.
+  #[doc(hidden)]
+  fn as_bytes_mut(_slice: &mut [Self]) -> Option<&mut [u8]> {
+    None
+  }
 }
 
+/// A marker trait for `BitBlock`s which can be safely reinterpreted from raw
+/// bytes.
+///
+/// # Safety
+///
+/// Implementors must have no padding bytes, and every bit pattern of
+/// `size_of::<Self>()` bytes must be a valid value of the type.
+pub unsafe trait PlainBlock: BitBlock {}
+
 macro_rules! impl_bit_block {
   ($type:ty) => {
     impl BitBlock for $type {
       const NUM_BITS: usize = std::mem::size_of::<$type>() * 8;
+
+      #[inline(always)]
+      fn zero() -> Self {
+        0
+      }
+
+      #[inline(always)]
+      fn one() -> Self {
+        1
+      }
+
+      #[inline(always)]
+      fn bit_and(self, other: Self) -> Self {
+        self & other
+      }
+
+      #[inline(always)]
+      fn bit_or(self, other: Self) -> Self {
+        self | other
+      }
+
+      #[inline(always)]
+      fn bit_xor(self, other: Self) -> Self {
+        self ^ other
+      }
+
+      #[inline(always)]
+      fn bit_not(self) -> Self {
+        !self
+      }
+
+      #[inline(always)]
+      fn count_ones(self) -> u32 {
+        <$type>::count_ones(self)
+      }
+
+      #[inline(always)]
+      fn trailing_zeros(self) -> u32 {
+        <$type>::trailing_zeros(self)
+      }
+
+      #[inline(always)]
+      fn leading_zeros(self) -> u32 {
+        <$type>::leading_zeros(self)
+      }
+
+      #[inline(always)]
+      fn shl_bits(self, n: usize) -> Self {
+        self << n
+      }
+
+      #[inline(always)]
+      fn shr_bits(self, n: usize) -> Self {
+        self >> n
+      }
+
+      #[inline(always)]
+      fn to_usize(self) -> usize {
+        self as usize
+      }
+
+      #[inline(always)]
+      fn from_usize(x: usize) -> Self {
+        x as $type
+      }
+
+      fn as_bytes(slice: &[Self]) -> Option<&[u8]> {
+        // SAFETY: integers have no padding bytes.
+        Some(unsafe {
+          std::slice::from_raw_parts(slice.as_ptr() as *const u8, std::mem::size_of_val(slice))
+        })
+      }
+
+      fn as_bytes_mut(slice: &mut [Self]) -> Option<&mut [u8]> {
+        // SAFETY: integers have no padding bytes, and every bit pattern is
+        // valid.
+        Some(unsafe {
+          std::slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut u8, std::mem::size_of_val(slice))
+        })
+      }
     }
+
+    unsafe impl PlainBlock for $type {}
   }
 }
 
@@ -23,4 +207,202 @@ impl_bit_block!(u16);
 impl_bit_block!(u32);
 impl_bit_block!(u64);
 impl_bit_block!(u128);
-impl_bit_block!(usize);
\ No newline at end of file
+impl_bit_block!(usize);
+
+impl<T, const N: usize> BitBlock for [T; N]
+where
+  T: BitBlock,
+  [T; N]: Default,
+{
+  const NUM_BITS: usize = {
+    assert!(N > 0, "empty array blocks are not supported");
+    N * T::NUM_BITS
+  };
+
+  #[inline]
+  fn zero() -> Self {
+    [T::zero(); N]
+  }
+
+  #[inline]
+  fn one() -> Self {
+    let mut blk = Self::zero();
+    blk[0] = T::one();
+    blk
+  }
+
+  #[inline]
+  fn bit_and(self, other: Self) -> Self {
+    std::array::from_fn(|i| self[i].bit_and(other[i]))
+  }
+
+  #[inline]
+  fn bit_or(self, other: Self) -> Self {
+    std::array::from_fn(|i| self[i].bit_or(other[i]))
+  }
+
+  #[inline]
+  fn bit_xor(self, other: Self) -> Self {
+    std::array::from_fn(|i| self[i].bit_xor(other[i]))
+  }
+
+  #[inline]
+  fn bit_not(self) -> Self {
+    self.map(T::bit_not)
+  }
+
+  #[inline]
+  fn count_ones(self) -> u32 {
+    self.iter().map(|x| x.count_ones()).sum()
+  }
+
+  #[inline]
+  fn trailing_zeros(self) -> u32 {
+    match self.iter().position(|&x| x != T::zero()) {
+      Some(i) => (i * T::NUM_BITS) as u32 + self[i].trailing_zeros(),
+      None => Self::NUM_BITS as u32,
+    }
+  }
+
+  #[inline]
+  fn leading_zeros(self) -> u32 {
+    match self.iter().rposition(|&x| x != T::zero()) {
+      Some(i) => ((N - 1 - i) * T::NUM_BITS) as u32 + self[i].leading_zeros(),
+      None => Self::NUM_BITS as u32,
+    }
+  }
+
+  fn shl_bits(self, n: usize) -> Self {
+    let (words, bits) = (n / T::NUM_BITS, n % T::NUM_BITS);
+    std::array::from_fn(|i| {
+      if i < words {
+        return T::zero();
+      }
+      let blk = self[i - words].shl_bits(bits);
+      if bits == 0 || i == words {
+        blk
+      } else {
+        blk.bit_or(self[i - words - 1].shr_bits(T::NUM_BITS - bits))
+      }
+    })
+  }
+
+  fn shr_bits(self, n: usize) -> Self {
+    let (words, bits) = (n / T::NUM_BITS, n % T::NUM_BITS);
+    std::array::from_fn(|i| {
+      if i + words >= N {
+        return T::zero();
+      }
+      let blk = self[i + words].shr_bits(bits);
+      if bits == 0 || i + words + 1 == N {
+        blk
+      } else {
+        blk.bit_or(self[i + words + 1].shl_bits(T::NUM_BITS - bits))
+      }
+    })
+  }
+
+  fn to_usize(self) -> usize {
+    (0..N)
+      .take_while(|&i| i * T::NUM_BITS < usize::BITS as usize)
+      .fold(0, |x, i| x | self[i].to_usize() << (i * T::NUM_BITS))
+  }
+
+  fn from_usize(x: usize) -> Self {
+    std::array::from_fn(|i| {
+      let shift = i * T::NUM_BITS;
+      if shift < usize::BITS as usize { T::from_usize(x >> shift) } else { T::zero() }
+    })
+  }
+
+  fn as_bytes(slice: &[Self]) -> Option<&[u8]> {
+    T::as_bytes(slice.as_flattened())
+  }
+
+  fn as_bytes_mut(slice: &mut [Self]) -> Option<&mut [u8]> {
+    T::as_bytes_mut(slice.as_flattened_mut())
+  }
+}
+
+// SAFETY: arrays have no padding between their elements.
+unsafe impl<T, const N: usize> PlainBlock for [T; N]
+where
+  T: PlainBlock,
+  [T; N]: Default,
+{}
+
+/// A guard giving mutable access to the blocks of the underlying bit vector of
+/// a `BitSet`.
+///
+/// This struct is created by the [`blocks_mut`] method on [`BitSet`]s. The
+/// invariants of the set are restored when the guard is dropped.
+///
+/// [`BitSet`]: struct.BitSet.html
+/// [`blocks_mut`]: struct.BitSet.html#method.blocks_mut
+pub struct BlocksMut<'a, T: BitBlock> {
+  set: &'a mut BitSet<T>,
+}
+
+impl<'a, T: BitBlock> BlocksMut<'a, T> {
+  pub(crate) fn new(set: &'a mut BitSet<T>) -> Self {
+    Self { set }
+  }
+}
+
+impl<'a, T: BitBlock> Deref for BlocksMut<'a, T> {
+  type Target = [T];
+
+  fn deref(&self) -> &[T] {
+    &self.set.vec
+  }
+}
+
+impl<'a, T: BitBlock> DerefMut for BlocksMut<'a, T> {
+  fn deref_mut(&mut self) -> &mut [T] {
+    &mut self.set.vec
+  }
+}
+
+impl<'a, T: BitBlock> Drop for BlocksMut<'a, T> {
+  fn drop(&mut self) {
+    self.set.compact();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  fn to_u32(blk: [u8; 4]) -> u32 {
+    u32::from_le_bytes(blk)
+  }
+
+  #[test]
+  fn array_block() {
+    let values = [0u32, 1, 0x80, 0x100, 0x8000_0000, 0x0f0f_f0f0, 0x1234_5678, u32::MAX];
+
+    for &x in &values {
+      let blk = x.to_le_bytes();
+      assert_eq!(to_u32(blk.bit_not()), !x);
+      assert_eq!(blk.count_ones(), x.count_ones());
+      assert_eq!(blk.trailing_zeros(), x.trailing_zeros());
+      assert_eq!(blk.leading_zeros(), x.leading_zeros());
+
+      for n in 0..32 {
+        assert_eq!(to_u32(blk.shl_bits(n)), x << n);
+        assert_eq!(to_u32(blk.shr_bits(n)), x >> n);
+      }
+
+      for &y in &values {
+        let other = y.to_le_bytes();
+        assert_eq!(to_u32(blk.bit_and(other)), x & y);
+        assert_eq!(to_u32(blk.bit_or(other)), x | y);
+        assert_eq!(to_u32(blk.bit_xor(other)), x ^ y);
+      }
+    }
+
+    assert_eq!(<[u8; 4]>::NUM_BITS, 32);
+    assert_eq!(to_u32(<[u8; 4]>::one()), 1);
+  }
+}
@@ -0,0 +1,310 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::Flatten;
+use std::ops::{
+  BitOr, BitOrAssign, BitAnd, BitAndAssign, Sub, SubAssign, BitXor, BitXorAssign, Not
+};
+use crate::{BitBlock, BitSet, Iter, Runs};
+
+/// A `BitSet` over the fixed universe `0..universe`, which supports
+/// complements.
+///
+/// The binary operators panic if the universes of the operands differ.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::BoundedBitSet;
+///
+/// let mut set = <BoundedBitSet>::new(8);
+/// set.insert(1).unwrap();
+/// set.insert(5).unwrap();
+/// assert!(set.insert(8).is_err());
+///
+/// assert_eq!(set.iter_absent().collect::<Vec<_>>(), vec![0, 2, 3, 4, 6, 7]);
+/// assert_eq!((!&set).iter().collect::<Vec<_>>(), vec![0, 2, 3, 4, 6, 7]);
+/// assert_eq!(&set | &!&set, BoundedBitSet::full(8));
+/// ```
+#[derive(Clone)]
+pub struct BoundedBitSet<T = usize> {
+  set: BitSet<T>,
+  universe: usize,
+}
+
+/// The error returned by [`BoundedBitSet::insert`] for values outside of the
+/// universe.
+///
+/// [`BoundedBitSet::insert`]: struct.BoundedBitSet.html#method.insert
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfRange {
+  /// The value inserted.
+  pub value: usize,
+  /// The size of the universe of the set.
+  pub universe: usize,
+}
+
+impl Display for OutOfRange {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "value {} out of the universe 0..{}", self.value, self.universe)
+  }
+}
+
+impl Error for OutOfRange {}
+
+impl<T> BoundedBitSet<T>
+where
+  T: BitBlock,
+{
+  /// Creates an empty set over the universe `0..universe`.
+  pub fn new(universe: usize) -> Self {
+    Self {
+      set: BitSet::new(),
+      universe,
+    }
+  }
+
+  /// Creates a set containing the whole universe `0..universe`.
+  pub fn full(universe: usize) -> Self {
+    Self {
+      set: BitSet::from_ranges(Some(0..universe)),
+      universe,
+    }
+  }
+
+  /// Creates a set over the universe `0..universe` from `set`. Returns `None`
+  /// if `set` has elements outside of the universe.
+  pub fn from_bitset(set: BitSet<T>, universe: usize) -> Option<Self> {
+    if set.end() > universe {
+      return None;
+    }
+    Some(Self { set, universe })
+  }
+
+  /// Returns the size of the universe.
+  pub fn universe(&self) -> usize {
+    self.universe
+  }
+
+  /// Returns the underlying unbounded set.
+  pub fn as_bitset(&self) -> &BitSet<T> {
+    &self.set
+  }
+
+  /// Converts the set into the underlying unbounded set.
+  pub fn into_bitset(self) -> BitSet<T> {
+    self.set
+  }
+
+  /// Iterates over the set, producing `usize`s representing the elements in
+  /// the set, in ascending order.
+  pub fn iter(&self) -> Iter<'_, T> {
+    self.set.iter()
+  }
+
+  /// Iterates over the elements of the universe absent from the set, in
+  /// ascending order.
+  pub fn iter_absent(&self) -> IterAbsent<'_, T> {
+    IterAbsent {
+      iter: self.set.runs_of_zeros(0..self.universe).flatten(),
+    }
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.set.len()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.set.is_empty()
+  }
+
+  /// Returns whether the set contains the whole universe.
+  pub fn is_full(&self) -> bool {
+    self.len() == self.universe
+  }
+
+  /// Clear the set, removing all elements.
+  pub fn clear(&mut self) {
+    self.set.clear();
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.set.contains(value)
+  }
+
+  /// Adds a value to the set. Returns whether the value was absent from the
+  /// set, or an error if the value is outside of the universe.
+  pub fn insert(&mut self, value: usize) -> Result<bool, OutOfRange> {
+    if value >= self.universe {
+      return Err(OutOfRange { value, universe: self.universe });
+    }
+    Ok(self.set.insert(value))
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&mut self, value: usize) -> bool {
+    self.set.remove(value)
+  }
+
+  /// Returns the elements of the universe absent from the set.
+  ///
+  /// A corresponding [Not](https://doc.rust-lang.org/std/ops/trait.Not.html) implementation is also available, i.e. `!a`.
+  pub fn complement(&self) -> Self {
+    let nblks = crate::compute_num_blocks::<T>(self.universe);
+    let mut vec = Vec::with_capacity(nblks);
+    vec.extend(self.set.as_blocks().iter().map(|blk| blk.bit_not()));
+    vec.resize(nblks, T::zero().bit_not());
+
+    if !self.universe.is_multiple_of(T::NUM_BITS) {
+      let last = vec.last_mut().unwrap();
+      *last = last.bit_and(T::zero().bit_not().shr_bits(T::NUM_BITS - self.universe % T::NUM_BITS));
+    }
+
+    Self {
+      set: BitSet::from_vec(vec),
+      universe: self.universe,
+    }
+  }
+
+  /// Complements the set in place.
+  pub fn complement_in_place(&mut self) {
+    *self = self.complement();
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the universes of the sets differ.
+  pub fn is_subset(&self, other: &Self) -> bool {
+    check_universe(self, other);
+    self.set.is_subset(&other.set)
+  }
+}
+
+#[inline]
+fn check_universe<T>(lhs: &BoundedBitSet<T>, rhs: &BoundedBitSet<T>) {
+  assert_eq!(lhs.universe, rhs.universe, "universes of the sets differ");
+}
+
+impl<T> Debug for BoundedBitSet<T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.set.fmt(f)
+  }
+}
+
+impl<T: BitBlock> PartialEq for BoundedBitSet<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.universe == other.universe && self.set == other.set
+  }
+}
+
+impl<T: BitBlock> Eq for BoundedBitSet<T> {}
+
+impl<T: BitBlock> Hash for BoundedBitSet<T> {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.universe.hash(state);
+    self.set.hash(state);
+  }
+}
+
+impl<'a, T> IntoIterator for &'a BoundedBitSet<T>
+where
+  T: BitBlock,
+{
+  type IntoIter = Iter<'a, T>;
+  type Item = usize;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<T: BitBlock> Not for BoundedBitSet<T> {
+  type Output = BoundedBitSet<T>;
+
+  fn not(self) -> BoundedBitSet<T> {
+    self.complement()
+  }
+}
+
+impl<T: BitBlock> Not for &BoundedBitSet<T> {
+  type Output = BoundedBitSet<T>;
+
+  fn not(self) -> BoundedBitSet<T> {
+    self.complement()
+  }
+}
+
+macro_rules! bounded_op_impl {
+  ($name:ident, $method:ident, $assign_name:ident, $assign_method:ident) => {
+    impl<T: BitBlock> $name<BoundedBitSet<T>> for BoundedBitSet<T> {
+      type Output = BoundedBitSet<T>;
+
+      fn $method(mut self, rhs: BoundedBitSet<T>) -> BoundedBitSet<T> {
+        self.$assign_method(rhs);
+        self
+      }
+    }
+
+    impl<'a, T: BitBlock> $name<&'a BoundedBitSet<T>> for &'a BoundedBitSet<T> {
+      type Output = BoundedBitSet<T>;
+
+      fn $method(self, rhs: &'a BoundedBitSet<T>) -> BoundedBitSet<T> {
+        check_universe(self, rhs);
+        BoundedBitSet {
+          set: (&self.set).$method(&rhs.set),
+          universe: self.universe,
+        }
+      }
+    }
+
+    impl<T: BitBlock> $assign_name<BoundedBitSet<T>> for BoundedBitSet<T> {
+      fn $assign_method(&mut self, rhs: BoundedBitSet<T>) {
+        check_universe(self, &rhs);
+        self.set.$assign_method(rhs.set);
+      }
+    }
+
+    impl<'a, T: BitBlock> $assign_name<&'a BoundedBitSet<T>> for BoundedBitSet<T> {
+      fn $assign_method(&mut self, rhs: &'a BoundedBitSet<T>) {
+        check_universe(self, rhs);
+        self.set.$assign_method(&rhs.set);
+      }
+    }
+  };
+}
+
+bounded_op_impl!(BitOr, bitor, BitOrAssign, bitor_assign);
+bounded_op_impl!(BitAnd, bitand, BitAndAssign, bitand_assign);
+bounded_op_impl!(Sub, sub, SubAssign, sub_assign);
+bounded_op_impl!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+/// An iterator over the elements absent from a `BoundedBitSet`.
+///
+/// This struct is created by the [`iter_absent`] method on
+/// [`BoundedBitSet`]s.
+///
+/// [`BoundedBitSet`]: struct.BoundedBitSet.html
+/// [`iter_absent`]: struct.BoundedBitSet.html#method.iter_absent
+pub struct IterAbsent<'a, T: BitBlock> {
+  iter: Flatten<Runs<'a, T>>,
+}
+
+impl<'a, T> Iterator for IterAbsent<'a, T>
+where
+  T: BitBlock,
+{
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    self.iter.next()
+  }
+}
@@ -0,0 +1,283 @@
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::{
+  BitOr, BitOrAssign, BitAnd, BitAndAssign, Sub, SubAssign, BitXor, BitXorAssign, Not
+};
+use crate::{BitBlock, BitSet, Iter};
+
+/// A set of naturals which is either finite, or cofinite, i.e. all naturals
+/// except a finite set, so that it is closed under complement.
+///
+/// It is stored as a `BitSet` and a flag telling whether the set is the
+/// complement of the `BitSet`, so the complement is computed in O(1).
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::{BitSet, CofiniteBitSet};
+///
+/// let evens = (0..10).step_by(2).collect::<BitSet>();
+/// let set1 = CofiniteBitSet::from_bitset(evens.clone());
+/// let set2 = CofiniteBitSet::cofinite(evens);
+///
+/// assert!(set2.contains(1000));
+/// assert!(!set2.contains(4));
+/// assert_eq!(&set1 | &set2, CofiniteBitSet::full());
+/// assert!((&set1 & &set2).is_empty());
+/// assert_eq!(!set1, set2);
+/// assert_eq!(format!("{:?}", set2), "!{0, 2, 4, 6, 8}");
+/// ```
+#[derive(Clone, Default)]
+pub struct CofiniteBitSet<T = usize> {
+  set: BitSet<T>,
+  complemented: bool,
+}
+
+impl<T> CofiniteBitSet<T>
+where
+  T: BitBlock,
+{
+  /// Creates an empty set.
+  pub fn new() -> Self {
+    Self::from_bitset(BitSet::new())
+  }
+
+  /// Creates the set of all naturals.
+  pub fn full() -> Self {
+    Self::cofinite(BitSet::new())
+  }
+
+  /// Creates a finite set from `set`.
+  pub fn from_bitset(set: BitSet<T>) -> Self {
+    Self {
+      set,
+      complemented: false,
+    }
+  }
+
+  /// Creates the cofinite set of all naturals except the elements of
+  /// `excluded`.
+  pub fn cofinite(excluded: BitSet<T>) -> Self {
+    Self {
+      set: excluded,
+      complemented: true,
+    }
+  }
+
+  /// Returns whether the set is cofinite.
+  pub fn is_cofinite(&self) -> bool {
+    self.complemented
+  }
+
+  /// Returns the set if it is finite.
+  pub fn as_finite(&self) -> Option<&BitSet<T>> {
+    if self.complemented {
+      None
+    } else {
+      Some(&self.set)
+    }
+  }
+
+  /// Returns the naturals absent from the set if it is cofinite.
+  pub fn as_excluded(&self) -> Option<&BitSet<T>> {
+    if self.complemented {
+      Some(&self.set)
+    } else {
+      None
+    }
+  }
+
+  /// Iterates over the set in ascending order if it is finite. Returns `None`
+  /// if the set is cofinite.
+  pub fn iter(&self) -> Option<Iter<'_, T>> {
+    self.as_finite().map(BitSet::iter)
+  }
+
+  /// Returns the number of elements of the set if it is finite.
+  pub fn len(&self) -> Option<usize> {
+    self.as_finite().map(BitSet::len)
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    !self.complemented && self.set.is_empty()
+  }
+
+  /// Returns whether the set contains all naturals.
+  pub fn is_full(&self) -> bool {
+    self.complemented && self.set.is_empty()
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.set.contains(value) != self.complemented
+  }
+
+  /// Adds a value to the set. Returns whether the value was absent from the
+  /// set.
+  pub fn insert(&mut self, value: usize) -> bool {
+    if self.complemented {
+      self.set.remove(value)
+    } else {
+      self.set.insert(value)
+    }
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&mut self, value: usize) -> bool {
+    if self.complemented {
+      self.set.insert(value)
+    } else {
+      self.set.remove(value)
+    }
+  }
+
+  /// Returns the complement of the set.
+  ///
+  /// A corresponding [Not](https://doc.rust-lang.org/std/ops/trait.Not.html) implementation is also available, i.e. `!a`.
+  pub fn complement(&self) -> Self {
+    !self.clone()
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  pub fn is_subset(&self, other: &Self) -> bool {
+    match (self.complemented, other.complemented) {
+      (false, false) => self.set.is_subset(&other.set),
+      (false, true) => self.set.intersection_len(&other.set) == 0,
+      (true, false) => false,
+      (true, true) => other.set.is_subset(&self.set),
+    }
+  }
+
+  fn union_assign(&mut self, rhs: &Self) {
+    match (self.complemented, rhs.complemented) {
+      (false, false) => self.set |= &rhs.set,
+      (false, true) => {
+        self.set = &rhs.set - &self.set;
+        self.complemented = true;
+      },
+      (true, false) => self.set -= &rhs.set,
+      (true, true) => self.set &= &rhs.set,
+    }
+  }
+
+  fn intersection_assign(&mut self, rhs: &Self) {
+    match (self.complemented, rhs.complemented) {
+      (false, false) => self.set &= &rhs.set,
+      (false, true) => self.set -= &rhs.set,
+      (true, false) => {
+        self.set = &rhs.set - &self.set;
+        self.complemented = false;
+      },
+      (true, true) => self.set |= &rhs.set,
+    }
+  }
+
+  fn difference_assign(&mut self, rhs: &Self) {
+    match (self.complemented, rhs.complemented) {
+      (false, false) => self.set -= &rhs.set,
+      (false, true) => self.set &= &rhs.set,
+      (true, false) => self.set |= &rhs.set,
+      (true, true) => {
+        self.set = &rhs.set - &self.set;
+        self.complemented = false;
+      },
+    }
+  }
+
+  fn symmetric_difference_assign(&mut self, rhs: &Self) {
+    self.set ^= &rhs.set;
+    self.complemented ^= rhs.complemented;
+  }
+}
+
+impl<T> Debug for CofiniteBitSet<T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    if self.complemented {
+      f.write_str("!")?;
+    }
+    self.set.fmt(f)
+  }
+}
+
+impl<T: BitBlock> PartialEq for CofiniteBitSet<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.complemented == other.complemented && self.set == other.set
+  }
+}
+
+impl<T: BitBlock> Eq for CofiniteBitSet<T> {}
+
+impl<T: BitBlock> Hash for CofiniteBitSet<T> {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.complemented.hash(state);
+    self.set.hash(state);
+  }
+}
+
+impl<T: BitBlock> From<BitSet<T>> for CofiniteBitSet<T> {
+  fn from(set: BitSet<T>) -> Self {
+    Self::from_bitset(set)
+  }
+}
+
+impl<T: BitBlock> Not for CofiniteBitSet<T> {
+  type Output = CofiniteBitSet<T>;
+
+  fn not(mut self) -> CofiniteBitSet<T> {
+    self.complemented = !self.complemented;
+    self
+  }
+}
+
+impl<T: BitBlock> Not for &CofiniteBitSet<T> {
+  type Output = CofiniteBitSet<T>;
+
+  fn not(self) -> CofiniteBitSet<T> {
+    self.complement()
+  }
+}
+
+macro_rules! cofinite_op_impl {
+  ($name:ident, $method:ident, $assign_name:ident, $assign_method:ident, $fn:ident) => {
+    impl<T: BitBlock> $name<CofiniteBitSet<T>> for CofiniteBitSet<T> {
+      type Output = CofiniteBitSet<T>;
+
+      fn $method(mut self, rhs: CofiniteBitSet<T>) -> CofiniteBitSet<T> {
+        self.$fn(&rhs);
+        self
+      }
+    }
+
+    impl<'a, T: BitBlock> $name<&'a CofiniteBitSet<T>> for &'a CofiniteBitSet<T> {
+      type Output = CofiniteBitSet<T>;
+
+      fn $method(self, rhs: &'a CofiniteBitSet<T>) -> CofiniteBitSet<T> {
+        let mut lhs = self.clone();
+        lhs.$fn(rhs);
+        lhs
+      }
+    }
+
+    impl<T: BitBlock> $assign_name<CofiniteBitSet<T>> for CofiniteBitSet<T> {
+      fn $assign_method(&mut self, rhs: CofiniteBitSet<T>) {
+        self.$fn(&rhs);
+      }
+    }
+
+    impl<'a, T: BitBlock> $assign_name<&'a CofiniteBitSet<T>> for CofiniteBitSet<T> {
+      fn $assign_method(&mut self, rhs: &'a CofiniteBitSet<T>) {
+        self.$fn(rhs);
+      }
+    }
+  };
+}
+
+cofinite_op_impl!(BitOr, bitor, BitOrAssign, bitor_assign, union_assign);
+cofinite_op_impl!(BitAnd, bitand, BitAndAssign, bitand_assign, intersection_assign);
+cofinite_op_impl!(Sub, sub, SubAssign, sub_assign, difference_assign);
+cofinite_op_impl!(BitXor, bitxor, BitXorAssign, bitxor_assign, symmetric_difference_assign);
@@ -0,0 +1,174 @@
+use std::fmt::{self, Debug, Formatter};
+use std::sync::OnceLock;
+use std::sync::atomic::Ordering;
+use crate::{AtomicBitBlock, BitSet};
+
+/// Number of blocks of the first segment. Each following segment is twice as
+/// large as the previous one.
+const FIRST_SEGMENT_BLOCKS: usize = 64;
+
+const NUM_SEGMENTS: usize = usize::BITS as usize;
+
+/// A growable bit set which can be modified concurrently.
+///
+/// The blocks are stored in an append-only table of segments of increasing
+/// sizes, which are allocated on the first insertion into them and never
+/// moved. `contains` and `remove` are wait-free. `insert` is lock-free, unless
+/// it has to allocate a segment, in which case concurrent insertions into the
+/// same segment wait for the allocation.
+///
+/// All operations use `AcqRel` for modifications and `Acquire` for loads.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::ConcurrentBitSet;
+///
+/// let set = ConcurrentBitSet::<u64>::new();
+///
+/// std::thread::scope(|s| {
+///   for t in 0..4 {
+///     let set = &set;
+///     s.spawn(move || {
+///       for i in (t..100000).step_by(4) {
+///         set.insert(i);
+///       }
+///     });
+///   }
+/// });
+///
+/// assert_eq!(set.snapshot(), (0..100000).collect());
+/// ```
+pub struct ConcurrentBitSet<T: AtomicBitBlock = usize> {
+  segments: [OnceLock<Box<[T::Atomic]>>; NUM_SEGMENTS],
+}
+
+impl<T> ConcurrentBitSet<T>
+where
+  T: AtomicBitBlock,
+{
+  /// Creates a new empty `ConcurrentBitSet`.
+  pub fn new() -> Self {
+    Self {
+      segments: std::array::from_fn(|_| OnceLock::new()),
+    }
+  }
+
+  /// Returns the segment, the index of the block within the segment, and the
+  /// bit mask for `value`.
+  #[inline(always)]
+  fn locate(value: usize) -> (usize, usize, T) {
+    let blk = value / T::NUM_BITS;
+    let seg = (blk / FIRST_SEGMENT_BLOCKS + 1).ilog2() as usize;
+    let offset = blk - segment_start(seg);
+    (seg, offset, T::one().shl_bits(value % T::NUM_BITS))
+  }
+
+  fn segment_or_init(&self, seg: usize) -> &[T::Atomic] {
+    self.segments[seg].get_or_init(|| {
+      (0..FIRST_SEGMENT_BLOCKS << seg).map(|_| T::new_atomic(T::zero())).collect()
+    })
+  }
+
+  /// Adds a value to the set. Returns whether the value was not present in
+  /// the set.
+  pub fn insert(&self, value: usize) -> bool {
+    let (seg, offset, mask) = Self::locate(value);
+    let segment = self.segment_or_init(seg);
+    T::fetch_or(&segment[offset], mask, Ordering::AcqRel).bit_and(mask) == T::zero()
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&self, value: usize) -> bool {
+    let (seg, offset, mask) = Self::locate(value);
+    match self.segments[seg].get() {
+      Some(segment) => {
+        T::fetch_and(&segment[offset], mask.bit_not(), Ordering::AcqRel).bit_and(mask) != T::zero()
+      }
+      None => false,
+    }
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    let (seg, offset, mask) = Self::locate(value);
+    match self.segments[seg].get() {
+      Some(segment) => T::load(&segment[offset], Ordering::Acquire).bit_and(mask) != T::zero(),
+      None => false,
+    }
+  }
+
+  /// Returns the number of elements in the set.
+  ///
+  /// The blocks are loaded one by one, so the result may not correspond to
+  /// any single state of the set if it is modified concurrently.
+  pub fn len(&self) -> usize {
+    self.segments.iter()
+      .filter_map(OnceLock::get)
+      .flat_map(|segment| segment.iter())
+      .map(|x| T::load(x, Ordering::Acquire).count_ones() as usize)
+      .sum()
+  }
+
+  /// Returns whether the set is empty.
+  ///
+  /// See [`len`](#method.len) for consistency under concurrent modifications.
+  pub fn is_empty(&self) -> bool {
+    self.segments.iter()
+      .filter_map(OnceLock::get)
+      .flat_map(|segment| segment.iter())
+      .all(|x| T::load(x, Ordering::Acquire) == T::zero())
+  }
+
+  /// Adds all the elements of `other` to the set.
+  ///
+  /// Each block of `other` is merged atomically, but the blocks are merged one
+  /// by one.
+  pub fn union_from(&self, other: &BitSet<T>) {
+    for (base, blk) in other.iter_blocks() {
+      let (seg, offset, _) = Self::locate(base);
+      let segment = self.segment_or_init(seg);
+      T::fetch_or(&segment[offset], blk, Ordering::AcqRel);
+    }
+  }
+
+  /// Copies the set into a `BitSet`.
+  ///
+  /// The blocks are loaded one by one, so the snapshot may not correspond to
+  /// any single state of the set if it is modified concurrently.
+  pub fn snapshot(&self) -> BitSet<T> {
+    let mut vec = vec![];
+    for (seg, segment) in self.segments.iter().enumerate() {
+      if let Some(segment) = segment.get() {
+        vec.resize(segment_start(seg), T::zero());
+        vec.extend(segment.iter().map(|x| T::load(x, Ordering::Acquire)));
+      }
+    }
+    BitSet::from_vec(vec)
+  }
+}
+
+/// Returns the index of the first block of the segment `seg`.
+#[inline(always)]
+fn segment_start(seg: usize) -> usize {
+  FIRST_SEGMENT_BLOCKS * ((1 << seg) - 1)
+}
+
+impl<T> Default for ConcurrentBitSet<T>
+where
+  T: AtomicBitBlock,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T> Debug for ConcurrentBitSet<T>
+where
+  T: AtomicBitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.snapshot().fmt(f)
+  }
+}
@@ -0,0 +1,210 @@
+//! Occurrence counting across sets with bit-sliced counters.
+//!
+//! The occurrences of the bit positions of a block are counted by a group of
+//! blocks, the planes, where the plane `j` holds the bit `j` of the counts of
+//! all the positions. Blocks of the input sets are added to the planes two at
+//! a time with a carry-save adder, so the counts are maintained with a few
+//! bitwise operations per block instead of per element.
+
+use crate::{BitBlock, BitSet};
+
+/// Returns the carry and the sum bits of `a + b + c`.
+#[inline(always)]
+fn csa<T: BitBlock>(a: T, b: T, c: T) -> (T, T) {
+  let u = a.bit_xor(b);
+  (a.bit_and(b).bit_or(u.bit_and(c)), u.bit_xor(c))
+}
+
+/// Adds `carry` to the counters given by `planes`.
+#[inline(always)]
+fn ripple<T: BitBlock>(planes: &mut [T], mut carry: T) {
+  for plane in planes {
+    if carry == T::zero() {
+      break;
+    }
+    let sum = plane.bit_xor(carry);
+    carry = plane.bit_and(carry);
+    *plane = sum;
+  }
+}
+
+/// Counts the occurrences of the bit positions in `sets`, a chunk of blocks at
+/// a time. `f` is called with the index of the first block of each chunk and
+/// the planes of the blocks of the chunk, `nplanes` per block.
+fn count_chunks<T, F>(sets: &[&[T]], nplanes: usize, mut f: F)
+where
+  T: BitBlock,
+  F: FnMut(usize, &[T]),
+{
+  let nblks = sets.iter().map(|blks| blks.len()).max().unwrap_or(0);
+  let chunk = (4096 / std::mem::size_of::<T>()).max(1);
+  let mut planes = vec![T::zero(); chunk * nplanes];
+
+  for start in (0..nblks).step_by(chunk) {
+    let len = chunk.min(nblks - start);
+    let planes = &mut planes[..len * nplanes];
+    planes.fill(T::zero());
+
+    for pair in sets.chunks(2) {
+      let a = pair[0].get(start..).unwrap_or(&[]);
+      let b = pair.get(1).and_then(|b| b.get(start..)).unwrap_or(&[]);
+
+      for (i, counter) in planes.chunks_exact_mut(nplanes).enumerate() {
+        let a = a.get(i).copied().unwrap_or(T::zero());
+        let b = b.get(i).copied().unwrap_or(T::zero());
+        let (carry, sum) = csa(counter[0], a, b);
+        counter[0] = sum;
+        ripple(&mut counter[1..], carry);
+      }
+    }
+
+    f(start, planes);
+  }
+}
+
+/// Returns the number of planes needed to count up to `n`.
+fn num_planes(n: usize) -> usize {
+  (usize::BITS - n.leading_zeros()) as usize
+}
+
+/// Returns the positions whose counts are greater than `k`, and those whose
+/// counts are equal to `k`.
+#[inline(always)]
+fn compare<T: BitBlock>(counter: &[T], k: usize) -> (T, T) {
+  let mut gt = T::zero();
+  let mut eq = T::zero().bit_not();
+
+  for (j, &plane) in counter.iter().enumerate().rev() {
+    if k >> j & 1 == 1 {
+      eq = eq.bit_and(plane);
+    } else {
+      gt = gt.bit_or(eq.bit_and(plane));
+      eq = eq.bit_and(plane.bit_not());
+    }
+  }
+
+  (gt, eq)
+}
+
+impl<T> BitSet<T>
+where
+  T: BitBlock,
+{
+  /// Returns the elements present in at least `k` of the `sets`.
+  ///
+  /// Only the elements less than the largest element of the `sets` are
+  /// considered, so with a `k` of zero, the result contains all of them.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let sets = vec![bitset![1,3,5], bitset![3,5,7], bitset![5,7,9]];
+  ///
+  /// assert_eq!(BitSet::at_least(2, &sets), bitset![3,5,7]);
+  /// assert_eq!(BitSet::at_least(3, &sets), bitset![5]);
+  /// ```
+  pub fn at_least<'a, I>(k: usize, sets: I) -> Self
+  where
+    I: IntoIterator<Item = &'a Self>,
+    T: 'a,
+  {
+    Self::count_threshold(k, sets, |gt, eq| gt.bit_or(eq))
+  }
+
+  /// Returns the elements present in exactly `k` of the `sets`.
+  ///
+  /// Only the elements less than the largest element of the `sets` are
+  /// considered, so with a `k` of zero, the result contains the elements
+  /// missing from all the `sets` below that bound.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let sets = vec![bitset![1,3,5], bitset![3,5,7], bitset![5,7,9]];
+  ///
+  /// assert_eq!(BitSet::exactly(1, &sets), bitset![1,9]);
+  /// assert_eq!(BitSet::exactly(2, &sets), bitset![3,7]);
+  /// ```
+  pub fn exactly<'a, I>(k: usize, sets: I) -> Self
+  where
+    I: IntoIterator<Item = &'a Self>,
+    T: 'a,
+  {
+    Self::count_threshold(k, sets, |_, eq| eq)
+  }
+
+  fn count_threshold<'a, I, F>(k: usize, sets: I, select: F) -> Self
+  where
+    I: IntoIterator<Item = &'a Self>,
+    T: 'a,
+    F: Fn(T, T) -> T,
+  {
+    let sets = sets.into_iter().collect::<Vec<_>>();
+    let num_bits = sets.iter().map(|set| set.end()).max().unwrap_or(0);
+    if k > sets.len() {
+      return Self::new();
+    }
+
+    let blks = sets.iter().map(|set| set.as_blocks()).collect::<Vec<_>>();
+    let nplanes = num_planes(sets.len()).max(1);
+    let mut vec = Vec::with_capacity(crate::compute_num_blocks::<T>(num_bits));
+
+    count_chunks(&blks, nplanes, |_, planes| {
+      vec.extend(planes.chunks_exact(nplanes).map(|counter| {
+        let (gt, eq) = compare(counter, k);
+        select(gt, eq)
+      }));
+    });
+
+    if num_bits % T::NUM_BITS != 0 {
+      let last = vec.last_mut().unwrap();
+      *last = last.bit_and(T::zero().bit_not().shr_bits(T::NUM_BITS - num_bits % T::NUM_BITS));
+    }
+
+    Self::from_vec(vec)
+  }
+
+  /// Returns the number of the `sets` each element is present in, indexed by
+  /// the elements, up to the largest element of the `sets`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let sets = vec![bitset![1,3,5], bitset![3,5,7], bitset![5,7,9]];
+  ///
+  /// assert_eq!(BitSet::column_counts(&sets), vec![0,1,0,2,0,3,0,2,0,1]);
+  /// ```
+  pub fn column_counts<'a, I>(sets: I) -> Vec<u32>
+  where
+    I: IntoIterator<Item = &'a Self>,
+    T: 'a,
+  {
+    let sets = sets.into_iter().collect::<Vec<_>>();
+    let num_bits = sets.iter().map(|set| set.end()).max().unwrap_or(0);
+    let blks = sets.iter().map(|set| set.as_blocks()).collect::<Vec<_>>();
+    let nplanes = num_planes(sets.len());
+    let mut counts = vec![0; num_bits];
+
+    count_chunks(&blks, nplanes, |start, planes| {
+      for (i, counter) in planes.chunks_exact(nplanes).enumerate() {
+        let base = (start + i) * T::NUM_BITS;
+        for (j, &plane) in counter.iter().enumerate() {
+          let mut plane = plane;
+          while plane != T::zero() {
+            let bit = plane.trailing_zeros() as usize;
+            counts[base + bit] += 1 << j;
+            plane = plane.bit_xor(T::one().shl_bits(bit));
+          }
+        }
+      }
+    });
+
+    counts
+  }
+}
@@ -0,0 +1,293 @@
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use crate::{trim_zero_blocks, BitBlock, BitSet};
+
+/// A multiset of `usize`s in which each element has a count of up to
+/// `2^k - 1`, stored as `k`-bit counters packed in blocks, as for reference
+/// counts or counting Bloom filters.
+///
+/// A block holds `T::NUM_BITS / k` counters, which never straddle blocks.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::{BitSet, CountingBitSet};
+///
+/// let mut counts = <CountingBitSet>::new(2);
+/// assert_eq!(counts.increment(5), Some(1));
+/// assert_eq!(counts.increment(5), Some(2));
+/// assert_eq!(counts.increment(5), Some(3));
+/// assert_eq!(counts.increment(5), None);
+/// assert_eq!(counts.increment(9), Some(1));
+/// assert_eq!(counts.decrement(9), Some(0));
+/// assert_eq!(counts.decrement(9), None);
+///
+/// let mut other = <CountingBitSet>::new(2);
+/// other.set_count(5, 1);
+/// other.set_count(7, 2);
+///
+/// assert_eq!(counts.count(5), 3);
+/// assert_eq!(counts.max(&other).support(), [5, 7].iter().cloned().collect::<BitSet>());
+/// assert_eq!(counts.min(&other).iter().collect::<Vec<_>>(), vec![(5, 1)]);
+/// assert_eq!(counts.sum(&other).iter().collect::<Vec<_>>(), vec![(5, 3), (7, 2)]);
+/// ```
+#[derive(Clone)]
+pub struct CountingBitSet<T = usize> {
+  vec: Vec<T>,
+  bits: usize,
+}
+
+impl<T> CountingBitSet<T>
+where
+  T: BitBlock,
+{
+  /// Creates an empty multiset of `bits`-bit counters.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `bits` is zero, greater than `T::NUM_BITS`, or not less than
+  /// the number of bits of `usize`.
+  pub fn new(bits: usize) -> Self {
+    assert!(
+      bits > 0 && bits <= T::NUM_BITS && bits < usize::BITS as usize,
+      "invalid counter width",
+    );
+    Self {
+      vec: vec![],
+      bits,
+    }
+  }
+
+  /// Returns the number of bits of each counter.
+  pub fn counter_bits(&self) -> usize {
+    self.bits
+  }
+
+  /// Returns the largest count of an element, `2^k - 1`.
+  pub fn max_count(&self) -> usize {
+    (1 << self.bits) - 1
+  }
+
+  /// Returns the count of `value`.
+  pub fn count(&self, value: usize) -> usize {
+    let (i, offset) = self.locate(value);
+    self.vec.get(i).map_or(0, |&blk| self.field(blk, offset))
+  }
+
+  /// Sets the count of `value`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `count` exceeds `max_count()`.
+  pub fn set_count(&mut self, value: usize, count: usize) {
+    assert!(count <= self.max_count(), "count out of range");
+    let (i, offset) = self.locate(value);
+    if i >= self.vec.len() {
+      if count == 0 {
+        return;
+      }
+      self.vec.resize(i + 1, T::zero());
+    }
+
+    let mask = self.mask().shl_bits(offset);
+    self.vec[i] = self.vec[i].bit_and(mask.bit_not()).bit_or(T::from_usize(count).shl_bits(offset));
+  }
+
+  /// Increments the count of `value`. Returns the new count, or `None` if the
+  /// count is already `max_count()`, in which case it is left unchanged.
+  pub fn increment(&mut self, value: usize) -> Option<usize> {
+    let count = self.count(value);
+    if count == self.max_count() {
+      return None;
+    }
+    self.set_count(value, count + 1);
+    Some(count + 1)
+  }
+
+  /// Decrements the count of `value`. Returns the new count, or `None` if the
+  /// count is already zero.
+  pub fn decrement(&mut self, value: usize) -> Option<usize> {
+    let count = self.count(value).checked_sub(1)?;
+    self.set_count(value, count);
+    Some(count)
+  }
+
+  /// Returns whether the count of every element is zero.
+  pub fn is_empty(&self) -> bool {
+    self.vec.iter().all(|&blk| blk == T::zero())
+  }
+
+  /// Clear the multiset, setting the count of every element to zero.
+  pub fn clear(&mut self) {
+    self.vec.clear();
+  }
+
+  /// Iterates over the elements with positive counts in ascending order,
+  /// producing `(usize, usize)`s of the element and its count.
+  pub fn iter(&self) -> Counts<'_, T> {
+    Counts {
+      set: self,
+      next: 0,
+    }
+  }
+
+  /// Returns the set of the elements with positive counts.
+  pub fn support(&self) -> BitSet<T> {
+    self.iter().map(|(value, _)| value).collect()
+  }
+
+  /// Returns the multiset of the minimum counts of each element in `self` and
+  /// `other`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the counter widths of the multisets differ.
+  pub fn min(&self, other: &Self) -> Self {
+    self.combine(other, usize::min)
+  }
+
+  /// Returns the multiset of the maximum counts of each element in `self` and
+  /// `other`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the counter widths of the multisets differ.
+  pub fn max(&self, other: &Self) -> Self {
+    self.combine(other, usize::max)
+  }
+
+  /// Returns the multiset of the sums of the counts of each element in `self`
+  /// and `other`, saturating at `max_count()`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the counter widths of the multisets differ.
+  pub fn sum(&self, other: &Self) -> Self {
+    let max_count = self.max_count();
+    self.combine(other, |x, y| (x + y).min(max_count))
+  }
+
+  fn combine<F>(&self, other: &Self, f: F) -> Self
+  where
+    F: Fn(usize, usize) -> usize,
+  {
+    assert_eq!(self.bits, other.bits, "counter widths of the sets differ");
+
+    let per = self.per_block();
+    let mut vec = vec![T::zero(); self.vec.len().max(other.vec.len())];
+    for (i, blk) in vec.iter_mut().enumerate() {
+      let x = self.vec.get(i).copied().unwrap_or(T::zero());
+      let y = other.vec.get(i).copied().unwrap_or(T::zero());
+      if x == T::zero() && y == T::zero() {
+        continue;
+      }
+      for offset in (0..per).map(|j| j * self.bits) {
+        let count = f(self.field(x, offset), self.field(y, offset));
+        *blk = blk.bit_or(T::from_usize(count).shl_bits(offset));
+      }
+    }
+
+    Self {
+      vec,
+      bits: self.bits,
+    }
+  }
+
+  #[inline(always)]
+  fn per_block(&self) -> usize {
+    T::NUM_BITS / self.bits
+  }
+
+  /// Returns the block index and the bit offset of the counter of `value`.
+  #[inline(always)]
+  fn locate(&self, value: usize) -> (usize, usize) {
+    let per = self.per_block();
+    (value / per, value % per * self.bits)
+  }
+
+  #[inline(always)]
+  fn mask(&self) -> T {
+    T::zero().bit_not().shr_bits(T::NUM_BITS - self.bits)
+  }
+
+  /// Returns the counter at `offset` of `blk`.
+  #[inline(always)]
+  fn field(&self, blk: T, offset: usize) -> usize {
+    blk.shr_bits(offset).bit_and(self.mask()).to_usize()
+  }
+}
+
+impl<T> Debug for CountingBitSet<T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_map().entries(self.iter()).finish()
+  }
+}
+
+impl<T: BitBlock> PartialEq for CountingBitSet<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.bits == other.bits && trim_zero_blocks(&self.vec) == trim_zero_blocks(&other.vec)
+  }
+}
+
+impl<T: BitBlock> Eq for CountingBitSet<T> {}
+
+impl<T: BitBlock> Hash for CountingBitSet<T> {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.bits.hash(state);
+    trim_zero_blocks(&self.vec).hash(state);
+  }
+}
+
+impl<'a, T> IntoIterator for &'a CountingBitSet<T>
+where
+  T: BitBlock,
+{
+  type IntoIter = Counts<'a, T>;
+  type Item = (usize, usize);
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+/// An iterator over the elements of a `CountingBitSet` and their counts.
+///
+/// This struct is created by the [`iter`] method on [`CountingBitSet`]s.
+///
+/// [`CountingBitSet`]: struct.CountingBitSet.html
+/// [`iter`]: struct.CountingBitSet.html#method.iter
+pub struct Counts<'a, T> {
+  set: &'a CountingBitSet<T>,
+  next: usize,
+}
+
+impl<'a, T> Iterator for Counts<'a, T>
+where
+  T: BitBlock,
+{
+  type Item = (usize, usize);
+
+  fn next(&mut self) -> Option<(usize, usize)> {
+    let per = self.set.per_block();
+    loop {
+      let (i, offset) = self.set.locate(self.next);
+      let blk = *self.set.vec.get(i)?;
+
+      // Skip the rest of the block if its remaining counters are all zero.
+      if blk.shr_bits(offset) == T::zero() {
+        self.next = (i + 1) * per;
+        continue;
+      }
+
+      let value = self.next;
+      self.next += 1;
+      let count = self.set.field(blk, offset);
+      if count > 0 {
+        return Some((value, count));
+      }
+    }
+  }
+}
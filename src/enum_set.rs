@@ -0,0 +1,373 @@
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops::{
+  BitOr, BitOrAssign, BitAnd, BitAndAssign, Sub, SubAssign, BitXor, BitXorAssign, Not
+};
+use crate::{BitBlock, BitSet};
+
+/// A trait for fieldless enums used as the elements of an [`EnumSet`].
+///
+/// The trait is normally implemented with `#[derive(BitSetKey)]`, available
+/// with the `derive` feature, which maps the variants to bit indices in
+/// declaration order, and chooses the smallest block type holding all the
+/// variants: an unsigned integer type up to `u128`, or an array of `u64`s.
+///
+/// [`EnumSet`]: struct.EnumSet.html
+pub trait BitSetKey: Copy {
+  /// The block type holding a bit for each variant.
+  type Block: BitBlock;
+
+  /// A type with a `const fn block(keys: &[Self]) -> Self::Block`, which
+  /// `enum_set!` uses to build the block of the keys in constant expressions.
+  #[doc(hidden)]
+  type ConstBlock;
+
+  /// The number of variants.
+  const COUNT: usize;
+
+  #[doc(hidden)]
+  const EMPTY: Self::Block;
+
+  #[doc(hidden)]
+  const ALL: Self::Block;
+
+  /// Returns the bit index of the variant, less than `COUNT`.
+  fn to_index(self) -> usize;
+
+  /// Returns the variant of the bit index.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `index` is not less than `COUNT`.
+  fn from_index(index: usize) -> Self;
+}
+
+/// A set of the variants of a fieldless enum, stored in a single block.
+///
+/// Constant sets can be created with the [`enum_set!`] macro.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use bittyset::{BitSetKey, EnumSet, enum_set};
+///
+/// #[derive(Clone, Copy, Debug, BitSetKey)]
+/// enum Permission {
+///   Read,
+///   Write,
+///   Exec,
+/// }
+///
+/// const READ_WRITE: EnumSet<Permission> = enum_set![Permission::Read, Permission::Write];
+///
+/// let mut set = EnumSet::empty();
+/// set.insert(Permission::Exec);
+///
+/// assert_eq!(set | READ_WRITE, EnumSet::all());
+/// assert_eq!(!set, READ_WRITE);
+/// assert!(READ_WRITE.contains(Permission::Write));
+/// assert_eq!(format!("{:?}", READ_WRITE), "{Read, Write}");
+/// assert_eq!(READ_WRITE.iter().collect::<Vec<_>>().len(), 2);
+/// ```
+///
+/// [`enum_set!`]: macro.enum_set.html
+pub struct EnumSet<K: BitSetKey> {
+  blk: K::Block,
+  marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K> EnumSet<K>
+where
+  K: BitSetKey,
+{
+  /// Creates an empty set.
+  pub const fn empty() -> Self {
+    Self::from_block_unchecked(K::EMPTY)
+  }
+
+  /// Creates an empty set.
+  pub const fn new() -> Self {
+    Self::empty()
+  }
+
+  /// Creates a set of all the variants.
+  pub const fn all() -> Self {
+    Self::from_block_unchecked(K::ALL)
+  }
+
+  #[doc(hidden)]
+  pub const fn from_block_unchecked(blk: K::Block) -> Self {
+    Self {
+      blk,
+      marker: PhantomData,
+    }
+  }
+
+  /// Creates a set from a block in which the bit indexed by `K::to_index(x)`
+  /// is set for each variant `x` in the set. The bits not belonging to any
+  /// variant are ignored.
+  pub fn from_block(blk: K::Block) -> Self {
+    Self::from_block_unchecked(blk.bit_and(K::ALL))
+  }
+
+  /// Returns the block in which the bit indexed by `K::to_index(x)` is set for
+  /// each variant `x` in the set.
+  pub fn as_block(&self) -> K::Block {
+    self.blk
+  }
+
+  /// Converts the set into a `BitSet` of the bit indices of the variants.
+  pub fn to_bitset(&self) -> BitSet<K::Block> {
+    BitSet::from_vec(vec![self.blk])
+  }
+
+  /// Iterates over the variants in the set, in ascending order of their bit
+  /// indices.
+  pub fn iter(&self) -> EnumSetIter<K> {
+    EnumSetIter {
+      blk: self.blk,
+      marker: PhantomData,
+    }
+  }
+
+  /// Returns the number of variants in the set.
+  pub fn len(&self) -> usize {
+    self.blk.count_ones() as usize
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.blk == K::EMPTY
+  }
+
+  /// Returns whether the set contains all the variants.
+  pub fn is_all(&self) -> bool {
+    self.blk == K::ALL
+  }
+
+  /// Clear the set, removing all variants.
+  pub fn clear(&mut self) {
+    self.blk = K::EMPTY;
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: K) -> bool {
+    self.blk.bit_and(bit::<K>(value)) != K::EMPTY
+  }
+
+  /// Adds a value to the set. Returns whether the value was absent from the
+  /// set.
+  pub fn insert(&mut self, value: K) -> bool {
+    let present = self.contains(value);
+    self.blk = self.blk.bit_or(bit::<K>(value));
+    !present
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&mut self, value: K) -> bool {
+    let present = self.contains(value);
+    self.blk = self.blk.bit_and(bit::<K>(value).bit_not());
+    present
+  }
+
+  /// Returns the set of the variants not in the set.
+  ///
+  /// A corresponding [Not](https://doc.rust-lang.org/std/ops/trait.Not.html) implementation is also available, i.e. `!a`.
+  pub fn complement(&self) -> Self {
+    Self::from_block_unchecked(self.blk.bit_not().bit_and(K::ALL))
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  pub fn is_subset(&self, other: &Self) -> bool {
+    self.blk.bit_and(other.blk.bit_not()) == K::EMPTY
+  }
+
+  /// Returns whether the set is a superset of `other`.
+  pub fn is_superset(&self, other: &Self) -> bool {
+    other.is_subset(self)
+  }
+
+  /// Returns whether the set has no variant in common with `other`.
+  pub fn is_disjoint(&self, other: &Self) -> bool {
+    self.blk.bit_and(other.blk) == K::EMPTY
+  }
+}
+
+#[inline(always)]
+fn bit<K: BitSetKey>(value: K) -> K::Block {
+  let index = value.to_index();
+  debug_assert!(index < K::COUNT);
+  K::Block::one().shl_bits(index)
+}
+
+impl<K: BitSetKey> Clone for EnumSet<K> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<K: BitSetKey> Copy for EnumSet<K> {}
+
+impl<K: BitSetKey> Default for EnumSet<K> {
+  fn default() -> Self {
+    Self::empty()
+  }
+}
+
+impl<K: BitSetKey> PartialEq for EnumSet<K> {
+  fn eq(&self, other: &Self) -> bool {
+    self.blk == other.blk
+  }
+}
+
+impl<K: BitSetKey> Eq for EnumSet<K> {}
+
+impl<K: BitSetKey> Hash for EnumSet<K> {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.blk.hash(state);
+  }
+}
+
+impl<K> Debug for EnumSet<K>
+where
+  K: BitSetKey + Debug,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_set().entries(self.iter()).finish()
+  }
+}
+
+impl<K: BitSetKey> From<K> for EnumSet<K> {
+  fn from(value: K) -> Self {
+    Self::from_block_unchecked(bit::<K>(value))
+  }
+}
+
+impl<K: BitSetKey> IntoIterator for EnumSet<K> {
+  type IntoIter = EnumSetIter<K>;
+  type Item = K;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<K: BitSetKey> IntoIterator for &EnumSet<K> {
+  type IntoIter = EnumSetIter<K>;
+  type Item = K;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<K: BitSetKey> FromIterator<K> for EnumSet<K> {
+  fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+    let mut set = Self::empty();
+    set.extend(iter);
+    set
+  }
+}
+
+impl<K: BitSetKey> Extend<K> for EnumSet<K> {
+  fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+    for x in iter {
+      self.insert(x);
+    }
+  }
+}
+
+impl<K: BitSetKey> Not for EnumSet<K> {
+  type Output = Self;
+
+  fn not(self) -> Self {
+    self.complement()
+  }
+}
+
+macro_rules! enum_set_op_impl {
+  ($name:ident, $method:ident, $assign_name:ident, $assign_method:ident, $op:expr) => {
+    impl<K: BitSetKey> $name<EnumSet<K>> for EnumSet<K> {
+      type Output = Self;
+
+      fn $method(self, rhs: Self) -> Self {
+        Self::from_block_unchecked($op(self.blk, rhs.blk))
+      }
+    }
+
+    impl<K: BitSetKey> $name<K> for EnumSet<K> {
+      type Output = Self;
+
+      fn $method(self, rhs: K) -> Self {
+        Self::from_block_unchecked($op(self.blk, bit::<K>(rhs)))
+      }
+    }
+
+    impl<K: BitSetKey> $assign_name<EnumSet<K>> for EnumSet<K> {
+      fn $assign_method(&mut self, rhs: Self) {
+        self.blk = $op(self.blk, rhs.blk);
+      }
+    }
+
+    impl<K: BitSetKey> $assign_name<K> for EnumSet<K> {
+      fn $assign_method(&mut self, rhs: K) {
+        self.blk = $op(self.blk, bit::<K>(rhs));
+      }
+    }
+  };
+}
+
+enum_set_op_impl!(BitOr, bitor, BitOrAssign, bitor_assign, BitBlock::bit_or);
+enum_set_op_impl!(BitAnd, bitand, BitAndAssign, bitand_assign, BitBlock::bit_and);
+enum_set_op_impl!(Sub, sub, SubAssign, sub_assign, |x: K::Block, y: K::Block| {
+  x.bit_and(y.bit_not())
+});
+enum_set_op_impl!(BitXor, bitxor, BitXorAssign, bitxor_assign, BitBlock::bit_xor);
+
+/// An iterator over the variants of an `EnumSet`.
+///
+/// This struct is created by the [`iter`] method on [`EnumSet`]s.
+///
+/// [`EnumSet`]: struct.EnumSet.html
+/// [`iter`]: struct.EnumSet.html#method.iter
+pub struct EnumSetIter<K: BitSetKey> {
+  blk: K::Block,
+  marker: PhantomData<fn() -> K>,
+}
+
+impl<K: BitSetKey> Iterator for EnumSetIter<K> {
+  type Item = K;
+
+  fn next(&mut self) -> Option<K> {
+    if self.blk == K::EMPTY {
+      return None;
+    }
+
+    let index = self.blk.trailing_zeros() as usize;
+    self.blk = self.blk.bit_xor(K::Block::one().shl_bits(index));
+    Some(K::from_index(index))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = self.blk.count_ones() as usize;
+    (len, Some(len))
+  }
+}
+
+impl<K: BitSetKey> DoubleEndedIterator for EnumSetIter<K> {
+  fn next_back(&mut self) -> Option<K> {
+    if self.blk == K::EMPTY {
+      return None;
+    }
+
+    let index = K::Block::NUM_BITS - 1 - self.blk.leading_zeros() as usize;
+    self.blk = self.blk.bit_xor(K::Block::one().shl_bits(index));
+    Some(K::from_index(index))
+  }
+}
+
+impl<K: BitSetKey> ExactSizeIterator for EnumSetIter<K> {}
@@ -0,0 +1,303 @@
+use std::ops::{BitOr, BitAnd, Sub, BitXor};
+use crate::{BitBlock, BitSet};
+use crate::simd::{self, Op};
+
+/// A lazy expression of set operations on `BitSet`s.
+///
+/// An expression is created with [`expr`] and combined with other sets or
+/// expressions with the `|`, `&`, `-` and `^` operators. No set is computed
+/// until the expression is evaluated, and then all the operations are fused:
+/// the blocks are computed a chunk at a time, and each operand is read only up
+/// to where it can affect the result.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::bitset;
+///
+/// let a = bitset![1,2,3,4];
+/// let b = bitset![5,6];
+/// let c = bitset![2,4,6,8];
+/// let d = bitset![4];
+///
+/// let expr = (a.expr() | &b) & (c.expr() - &d);
+///
+/// assert_eq!(expr.len(), 2);
+/// assert_eq!(expr.iter().collect::<Vec<_>>(), vec![2, 6]);
+/// assert_eq!(expr.evaluate(), bitset![2,6]);
+/// ```
+///
+/// [`expr`]: struct.BitSet.html#method.expr
+#[derive(Clone)]
+pub struct Expr<'a, T> {
+  node: Node<'a, T>,
+}
+
+#[derive(Clone)]
+enum Node<'a, T> {
+  Set(&'a [T]),
+  Op(Op, Box<Node<'a, T>>, Box<Node<'a, T>>),
+}
+
+impl<'a, T> Node<'a, T>
+where
+  T: BitBlock,
+{
+  /// Returns the number of blocks past which the node is zero.
+  fn num_blocks(&self) -> usize {
+    match self {
+      Node::Set(blks) => blks.len(),
+      Node::Op(op, lhs, rhs) => match op {
+        Op::And => lhs.num_blocks().min(rhs.num_blocks()),
+        Op::AndNot | Op::First => lhs.num_blocks(),
+        Op::Or | Op::Xor => lhs.num_blocks().max(rhs.num_blocks()),
+      },
+    }
+  }
+
+  /// Returns the number of chunk buffers needed to evaluate the node.
+  fn depth(&self) -> usize {
+    match self {
+      Node::Set(_) => 0,
+      Node::Op(_, lhs, rhs) => match **rhs {
+        Node::Set(_) => lhs.depth(),
+        _ => lhs.depth().max(rhs.depth() + 1),
+      },
+    }
+  }
+
+  /// Computes the blocks starting at `start` into `out`, using `scratch` for
+  /// the chunk buffers of the subexpressions. `scratch` must hold `depth`
+  /// buffers of the length of `out`.
+  fn eval(&self, start: usize, out: &mut [T], scratch: &mut [T]) {
+    let (op, lhs, rhs) = match self {
+      Node::Set(blks) => {
+        let blks = blks.get(start..).unwrap_or(&[]);
+        let n = out.len().min(blks.len());
+        out[..n].copy_from_slice(&blks[..n]);
+        out[n..].fill(T::zero());
+        return;
+      },
+      Node::Op(op, lhs, rhs) => (*op, lhs, rhs),
+    };
+
+    let end = rhs.num_blocks().saturating_sub(start).min(out.len());
+    if op == Op::And && end == 0 {
+      out.fill(T::zero());
+      return;
+    }
+
+    lhs.eval(start, out, scratch);
+
+    if op == Op::And {
+      out[end..].fill(T::zero());
+    }
+    if end == 0 {
+      return;
+    }
+
+    match **rhs {
+      Node::Set(blks) => apply(op, &mut out[..end], &blks[start..]),
+      _ => {
+        let (buf, scratch) = scratch.split_at_mut(end);
+        rhs.eval(start, buf, scratch);
+        apply(op, &mut out[..end], buf);
+      },
+    }
+  }
+}
+
+fn apply<T: BitBlock>(op: Op, dst: &mut [T], src: &[T]) {
+  match op {
+    Op::First => {},
+    Op::Or => simd::apply_assign::<T, simd::Or>(dst, src),
+    Op::And => simd::apply_assign::<T, simd::And>(dst, src),
+    Op::AndNot => simd::apply_assign::<T, simd::AndNot>(dst, src),
+    Op::Xor => simd::apply_assign::<T, simd::Xor>(dst, src),
+  }
+}
+
+/// Returns the number of blocks of a chunk.
+fn chunk_len<T>() -> usize {
+  (4096 / std::mem::size_of::<T>()).max(1)
+}
+
+impl<'a, T> Expr<'a, T>
+where
+  T: BitBlock,
+{
+  /// Calls `f` with the index of the first block of each chunk of the
+  /// expression and the blocks of the chunk, until `f` returns `false`.
+  fn for_each_chunk<F>(&self, mut f: F)
+  where
+    F: FnMut(usize, &[T]) -> bool,
+  {
+    let chunk = chunk_len::<T>();
+    let mut buf = vec![T::zero(); chunk.min(self.node.num_blocks())];
+    let mut scratch = vec![T::zero(); buf.len() * self.node.depth()];
+
+    for start in (0..self.node.num_blocks()).step_by(chunk) {
+      let len = chunk.min(self.node.num_blocks() - start);
+      self.node.eval(start, &mut buf[..len], &mut scratch);
+      if !f(start, &buf[..len]) {
+        return;
+      }
+    }
+  }
+
+  /// Computes the set of the expression.
+  pub fn evaluate(&self) -> BitSet<T> {
+    let nblks = self.node.num_blocks();
+    let mut vec = vec![T::zero(); nblks];
+    let mut scratch = vec![T::zero(); chunk_len::<T>().min(nblks) * self.node.depth()];
+
+    for (i, chunk) in vec.chunks_mut(chunk_len::<T>()).enumerate() {
+      self.node.eval(i * chunk_len::<T>(), chunk, &mut scratch);
+    }
+
+    BitSet::from_vec(vec)
+  }
+
+  /// Returns the number of elements of the set of the expression.
+  pub fn len(&self) -> usize {
+    let mut len = 0;
+    self.for_each_chunk(|_, blks| {
+      len += simd::count_ones(blks);
+      true
+    });
+    len
+  }
+
+  /// Returns whether the set of the expression is empty.
+  pub fn is_empty(&self) -> bool {
+    !self.any()
+  }
+
+  /// Returns whether the set of the expression has any element. The
+  /// evaluation stops at the first element found.
+  pub fn any(&self) -> bool {
+    let mut any = false;
+    self.for_each_chunk(|_, blks| {
+      any = blks.iter().any(|&blk| blk != T::zero());
+      !any
+    });
+    any
+  }
+
+  /// Iterates over the set of the expression in ascending order, evaluating
+  /// a chunk of blocks at a time.
+  pub fn iter(&self) -> ExprIter<'a, T> {
+    ExprIter {
+      expr: self.clone(),
+      scratch: vec![T::zero(); chunk_len::<T>() * self.node.depth()],
+      buf: vec![],
+      start: 0,
+      index: 0,
+      blk: T::zero(),
+    }
+  }
+
+  fn op(self, op: Op, rhs: Self) -> Self {
+    Self {
+      node: Node::Op(op, Box::new(self.node), Box::new(rhs.node)),
+    }
+  }
+}
+
+impl<T> BitSet<T>
+where
+  T: BitBlock,
+{
+  /// Returns a lazy expression of the set, to be combined with other sets.
+  ///
+  /// See [`Expr`](struct.Expr.html) for details.
+  pub fn expr(&self) -> Expr<'_, T> {
+    Expr {
+      node: Node::Set(&self.vec),
+    }
+  }
+}
+
+impl<'a, T> From<&'a BitSet<T>> for Expr<'a, T>
+where
+  T: BitBlock,
+{
+  fn from(set: &'a BitSet<T>) -> Self {
+    set.expr()
+  }
+}
+
+macro_rules! expr_op_impl {
+  ($name:ident, $method:ident, $op:ident) => {
+    impl<'a, T: BitBlock> $name<Expr<'a, T>> for Expr<'a, T> {
+      type Output = Expr<'a, T>;
+
+      fn $method(self, rhs: Expr<'a, T>) -> Expr<'a, T> {
+        self.op(Op::$op, rhs)
+      }
+    }
+
+    impl<'a, T: BitBlock> $name<&'a BitSet<T>> for Expr<'a, T> {
+      type Output = Expr<'a, T>;
+
+      fn $method(self, rhs: &'a BitSet<T>) -> Expr<'a, T> {
+        self.op(Op::$op, rhs.expr())
+      }
+    }
+  };
+}
+
+expr_op_impl!(BitOr, bitor, Or);
+expr_op_impl!(BitAnd, bitand, And);
+expr_op_impl!(Sub, sub, AndNot);
+expr_op_impl!(BitXor, bitxor, Xor);
+
+/// An iterator over the set of an [`Expr`].
+///
+/// This struct is created by the [`iter`] method on [`Expr`]s.
+///
+/// [`Expr`]: struct.Expr.html
+/// [`iter`]: struct.Expr.html#method.iter
+pub struct ExprIter<'a, T> {
+  expr: Expr<'a, T>,
+  scratch: Vec<T>,
+  /// The current chunk of blocks.
+  buf: Vec<T>,
+  /// The index of the first block of `buf`.
+  start: usize,
+  /// The index of the current block in `buf`.
+  index: usize,
+  /// The bits of the current block not yet yielded.
+  blk: T,
+}
+
+impl<'a, T> Iterator for ExprIter<'a, T>
+where
+  T: BitBlock,
+{
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    while self.blk == T::zero() {
+      self.index += 1;
+      if self.index >= self.buf.len() {
+        let start = self.start + self.buf.len();
+        let nblks = self.expr.node.num_blocks();
+        if start >= nblks {
+          return None;
+        }
+
+        let len = chunk_len::<T>().min(nblks - start);
+        self.buf.resize(len, T::zero());
+        self.expr.node.eval(start, &mut self.buf, &mut self.scratch);
+        self.start = start;
+        self.index = 0;
+      }
+      self.blk = self.buf[self.index];
+    }
+
+    let bit = self.blk.trailing_zeros() as usize;
+    self.blk = self.blk.bit_xor(T::one().shl_bits(bit));
+    Some((self.start + self.index) * T::NUM_BITS + bit)
+  }
+}
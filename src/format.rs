@@ -0,0 +1,81 @@
+//! The serialized form of bit sets, shared by `MmapBitSet` and the block
+//! streams.
+//!
+//! A serialized bit set consists of a `Header` followed by `num_blocks`
+//! blocks in native byte order. Trailing zero blocks are allowed.
+
+use std::io::{self, Read, Write};
+use std::mem;
+use std::ptr;
+
+const MAGIC: [u8; 8] = *b"BITTYSET";
+const VERSION: u32 = 1;
+const BYTE_ORDER: u64 = 0x0102_0304_0506_0708;
+pub(crate) const HEADER_LEN: usize = mem::size_of::<Header>();
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct Header {
+  magic: [u8; 8],
+  version: u32,
+  /// Size of a block in bytes.
+  block_size: u32,
+  /// Number of blocks following the header.
+  pub(crate) num_blocks: u64,
+  /// `BYTE_ORDER` in the byte order of the writer.
+  byte_order: u64,
+}
+
+impl Header {
+  pub(crate) fn new<T>(num_blocks: usize) -> Self {
+    Self {
+      magic: MAGIC,
+      version: VERSION,
+      block_size: mem::size_of::<T>() as u32,
+      num_blocks: num_blocks as u64,
+      byte_order: BYTE_ORDER,
+    }
+  }
+
+  /// Reads and validates a header for blocks of type `T`.
+  pub(crate) fn read<T, R: Read>(reader: &mut R) -> io::Result<Self> {
+    let mut buf = [0u8; HEADER_LEN];
+    reader.read_exact(&mut buf)?;
+    // SAFETY: `Header` is a plain C struct of integers.
+    let header = unsafe { ptr::read_unaligned(buf.as_ptr() as *const Header) };
+
+    let err = |msg: &str| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+
+    if header.magic != MAGIC {
+      return err("not a bittyset file");
+    }
+    if header.version != VERSION {
+      return err("unsupported bittyset file version");
+    }
+    if header.byte_order != BYTE_ORDER {
+      return err("bittyset file was written with a different byte order");
+    }
+    if header.block_size as usize != mem::size_of::<T>() {
+      return err("bittyset file was written with a different block type");
+    }
+    if usize::try_from(header.num_blocks).is_err() {
+      return err("bittyset file is too large");
+    }
+    Ok(header)
+  }
+
+  pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    // SAFETY: `Header` is a plain C struct of integers without padding.
+    let buf = unsafe {
+      std::slice::from_raw_parts(self as *const Header as *const u8, HEADER_LEN)
+    };
+    writer.write_all(buf)
+  }
+
+  /// Returns the length in bytes of the serialized set, including the header.
+  pub(crate) fn byte_len(&self) -> Option<u64> {
+    self.num_blocks
+      .checked_mul(self.block_size as u64)
+      .and_then(|x| x.checked_add(HEADER_LEN as u64))
+  }
+}
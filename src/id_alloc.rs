@@ -0,0 +1,204 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Range;
+use crate::{BitBlock, BitSet, Iter};
+
+/// An allocator of `usize` ids, handing out the lowest free id.
+///
+/// The allocated ids are stored in a `BitSet`, above which a hierarchy of
+/// summaries keeps a bit for each block of the level below, set if the block
+/// is full. Finding the lowest free id descends the summaries, so it takes
+/// `O(log n)` time.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::IdAllocator;
+///
+/// let mut ids = <IdAllocator>::new();
+/// ids.alloc_range(0..3);
+///
+/// assert_eq!(ids.alloc(), 3);
+/// assert!(ids.free(1));
+/// assert_eq!(ids.alloc(), 1);
+/// assert!(ids.alloc_at(4));
+/// assert_eq!(ids.alloc(), 5);
+/// assert_eq!(ids.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+/// ```
+#[derive(Clone, Default)]
+pub struct IdAllocator<T = usize> {
+  set: BitSet<T>,
+  /// The summaries from the lowest up. The bit `j` of a level is set if the
+  /// block `j` of the level below is full. The top level has at most one
+  /// block.
+  summaries: Vec<Vec<T>>,
+  len: usize,
+}
+
+impl<T> IdAllocator<T>
+where
+  T: BitBlock,
+{
+  /// Creates an allocator with no ids allocated.
+  pub fn new() -> Self {
+    Self {
+      set: BitSet::new(),
+      summaries: vec![],
+      len: 0,
+    }
+  }
+
+  /// Returns the set of the allocated ids.
+  pub fn as_bitset(&self) -> &BitSet<T> {
+    &self.set
+  }
+
+  /// Iterates over the allocated ids in ascending order.
+  pub fn iter(&self) -> Iter<'_, T> {
+    self.set.iter()
+  }
+
+  /// Returns the number of allocated ids.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Returns whether no id is allocated.
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns whether `id` is allocated.
+  pub fn is_allocated(&self, id: usize) -> bool {
+    self.set.contains(id)
+  }
+
+  /// Allocates the lowest free id and returns it.
+  pub fn alloc(&mut self) -> usize {
+    let id = self.first_free();
+    self.alloc_at(id);
+    id
+  }
+
+  /// Allocates `id`. Returns whether it was free.
+  pub fn alloc_at(&mut self, id: usize) -> bool {
+    if !self.set.insert(id) {
+      return false;
+    }
+    self.len += 1;
+    self.update(id / T::NUM_BITS);
+    true
+  }
+
+  /// Allocates all the ids in `range`.
+  pub fn alloc_range(&mut self, range: Range<usize>) {
+    if range.is_empty() {
+      return;
+    }
+
+    let allocated = self.set.iter_from(range.start).take_while(|&id| id < range.end).count();
+    self.len += range.len() - allocated;
+    self.set.extend_ranges(Some(range.clone()));
+    for i in range.start / T::NUM_BITS..=(range.end - 1) / T::NUM_BITS {
+      self.update(i);
+    }
+  }
+
+  /// Frees `id`. Returns whether it was allocated.
+  pub fn free(&mut self, id: usize) -> bool {
+    if !self.set.remove(id) {
+      return false;
+    }
+    self.len -= 1;
+    self.update(id / T::NUM_BITS);
+    true
+  }
+
+  /// Frees all the ids.
+  pub fn clear(&mut self) {
+    self.set.clear();
+    self.summaries.clear();
+    self.len = 0;
+  }
+
+  fn first_free(&self) -> usize {
+    // Trailing zero blocks are free like the blocks past the end, so the
+    // blocks need no trimming.
+    let blocks = &self.set.vec[..];
+    let block = |level: usize, index: usize| {
+      let level = if level == 0 { blocks } else { &self.summaries[level - 1][..] };
+      level.get(index).copied().unwrap_or(T::zero())
+    };
+
+    let top = self.summaries.len();
+    if block(top, 0) == T::zero().bit_not() {
+      // Every block is full, and the top block covers `NUM_BITS ^ (top + 1)`
+      // ids.
+      return (0..=top).fold(1, |n, _| n * T::NUM_BITS);
+    }
+
+    // The blocks past the end of a level are free.
+    (0..=top).rev().fold(0, |index, level| {
+      index * T::NUM_BITS + block(level, index).bit_not().trailing_zeros() as usize
+    })
+  }
+
+  /// Updates the summaries after the block `index` of the set changed.
+  fn update(&mut self, mut index: usize) {
+    let mut full = self.set.vec.get(index) == Some(&T::zero().bit_not());
+
+    for summary in self.summaries.iter_mut() {
+      let (i, bit) = (index / T::NUM_BITS, T::one().shl_bits(index % T::NUM_BITS));
+      if summary.len() <= i {
+        summary.resize(i + 1, T::zero());
+      }
+
+      // Go through all the levels even if the bit is unchanged, so that each
+      // level covers the blocks of the level below.
+      let old = summary[i];
+      summary[i] = if full { old.bit_or(bit) } else { old.bit_and(bit.bit_not()) };
+      full = summary[i] == T::zero().bit_not();
+      index = i;
+    }
+
+    // Add levels until the top one has at most one block.
+    loop {
+      let below = match self.summaries.last() {
+        Some(summary) => &summary[..],
+        None => &self.set.vec[..],
+      };
+      if below.len() <= 1 {
+        break;
+      }
+
+      let mut summary = vec![T::zero(); below.len().div_ceil(T::NUM_BITS)];
+      for (j, blk) in below.iter().enumerate() {
+        if *blk == T::zero().bit_not() {
+          let s = &mut summary[j / T::NUM_BITS];
+          *s = s.bit_or(T::one().shl_bits(j % T::NUM_BITS));
+        }
+      }
+      self.summaries.push(summary);
+    }
+  }
+}
+
+impl<T> Debug for IdAllocator<T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.set.fmt(f)
+  }
+}
+
+impl<'a, T> IntoIterator for &'a IdAllocator<T>
+where
+  T: BitBlock,
+{
+  type IntoIter = Iter<'a, T>;
+  type Item = usize;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
@@ -1,10 +1,11 @@
 use std::fmt::{self, Debug, Formatter};
-use std::iter::{FromIterator, Extend};
+use std::iter::{FromIterator, Extend, Sum, Product};
 use std::hash::{Hash, Hasher};
 use std::ops::{
   BitOr, BitOrAssign, BitAnd, BitAndAssign, Sub, SubAssign, BitXor, BitXorAssign
 };
 use crate::{BitBlock, BitSet, Iter};
+use crate::simd;
 
 impl<T> Debug for BitSet<T>
 where
@@ -43,23 +44,73 @@ impl<T: BitBlock> Extend<usize> for BitSet<T> {
   }
 }
 
+/// Collects the union of the sets.
+impl<T: BitBlock> FromIterator<BitSet<T>> for BitSet<T> {
+  fn from_iter<I: IntoIterator<Item = BitSet<T>>>(iter: I) -> Self {
+    let sets = iter.into_iter().collect::<Vec<_>>();
+    Self::union_all(&sets)
+  }
+}
+
+/// Collects the union of the sets.
+impl<'a, T: BitBlock> FromIterator<&'a BitSet<T>> for BitSet<T> {
+  fn from_iter<I: IntoIterator<Item = &'a BitSet<T>>>(iter: I) -> Self {
+    Self::union_all(iter)
+  }
+}
+
+/// Computes the union of the sets.
+impl<T: BitBlock> Sum for BitSet<T> {
+  fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+    iter.collect()
+  }
+}
+
+/// Computes the union of the sets.
+impl<'a, T: BitBlock> Sum<&'a BitSet<T>> for BitSet<T> {
+  fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+    Self::union_all(iter)
+  }
+}
+
+/// Computes the intersection of the sets, or an empty set if there are no
+/// sets.
+impl<T: BitBlock> Product for BitSet<T> {
+  fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+    let sets = iter.collect::<Vec<_>>();
+    Self::intersection_all(&sets)
+  }
+}
+
+/// Computes the intersection of the sets, or an empty set if there are no
+/// sets.
+impl<'a, T: BitBlock> Product<&'a BitSet<T>> for BitSet<T> {
+  fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+    Self::intersection_all(iter)
+  }
+}
+
 impl<T: BitBlock> PartialEq<BitSet<T>> for BitSet<T> {
   fn eq(&self, other: &BitSet<T>) -> bool {
-    if self.num_bits != other.num_bits {
-      return false;
-    }
+    let (short, long) = if self.vec.len() < other.vec.len() {
+      (&self.vec, &other.vec)
+    } else {
+      (&other.vec, &self.vec)
+    };
 
-    let nblks = crate::compute_num_blocks::<T>(self.num_bits);
-    self.vec[..nblks] == other.vec[..nblks]
+    simd::eq(short, &long[..short.len()]) &&
+      long[short.len()..].iter().all(|&blk| blk == T::zero())
   }
 }
 
 impl<T: BitBlock> Eq for BitSet<T> {}
 
+/// Hashes the blocks up to the last non-zero one. Like `as_blocks`, this skips
+/// the trailing zero blocks left by the set operations on every call, which
+/// takes time proportional to their number.
 impl<T: BitBlock> Hash for BitSet<T> {
   fn hash<H: Hasher>(&self, state: &mut H) {
-    let nblks = crate::compute_num_blocks::<T>(self.num_bits);
-    self.vec[..nblks].hash(state);
+    self.as_blocks().hash(state);
   }
 }
 
@@ -184,9 +235,7 @@ op_impl!{
   (lhs, rhs_vec, rhs_nbits) {
     let nblks = crate::compute_num_blocks::<T>(lhs.num_bits.min(rhs_nbits));
 
-    for i in 0..nblks {
-      lhs.vec[i] |= rhs_vec[i];
-    }
+    simd::apply_assign::<T, simd::Or>(&mut lhs.vec[..nblks], rhs_vec);
   }
 }
 
@@ -197,11 +246,7 @@ op_impl!{
   (lhs, rhs_vec, rhs_nbits) {
     let nblks = crate::compute_num_blocks::<T>(lhs.num_bits.min(rhs_nbits));
 
-    for i in 0..nblks {
-      lhs.vec[i] &= rhs_vec[i];
-    }
-
-    lhs.compact();
+    simd::apply_assign::<T, simd::And>(&mut lhs.vec[..nblks], rhs_vec);
   }
 }
 
@@ -212,11 +257,7 @@ op_impl!{
   (lhs, rhs_vec, rhs_nbits) {
     let nblks = crate::compute_num_blocks::<T>(lhs.num_bits.min(rhs_nbits));
 
-    for i in 0..nblks {
-      lhs.vec[i] ^= rhs_vec[i];
-    }
-
-    lhs.compact();
+    simd::apply_assign::<T, simd::Xor>(&mut lhs.vec[..nblks], rhs_vec);
   }
 }
 
@@ -226,10 +267,6 @@ op_impl!{
   (lhs, rhs_vec, rhs_nbits) {
     let nblks = crate::compute_num_blocks::<T>(lhs.num_bits.min(rhs_nbits));
 
-    for i in 0..nblks {
-      lhs.vec[i] &= !rhs_vec[i];
-    }
-
-    lhs.compact();
+    simd::apply_assign::<T, simd::AndNot>(&mut lhs.vec[..nblks], rhs_vec);
   }
 }
\ No newline at end of file
@@ -1,3 +1,6 @@
+use std::iter::Enumerate;
+use std::ops::Range;
+use std::slice;
 use crate::{BitBlock, BitSet};
 
 /// An iterator for `BitSet`.
@@ -18,13 +21,18 @@ where
   T: BitBlock
 {
   pub(crate) fn new(set: &'a BitSet<T>) -> Self {
+    Self::from_slice(&set.vec, set.num_bits)
+  }
+
+  pub(crate) fn from_slice(slice: &'a [T], num_bits: usize) -> Self {
     Self {
-      slice: &set.vec,
-      num_bits: set.num_bits,
+      slice,
+      num_bits,
       index: 0,
       bit: 0,
     }
   }
+
   pub(crate) fn new_from(set: &'a BitSet<T>, start: usize) -> Self {
     let (index, bit) = (start / T::NUM_BITS, start % T::NUM_BITS);
     Self {
@@ -63,10 +71,171 @@ fn find_lowest_set_bit<T: BitBlock>(blk: T, from: usize) -> Option<usize> {
     return None;
   }
 
-  let x = (blk & !((T::one() << from) - T::one())).trailing_zeros() as usize;
+  let x = blk.bit_and(T::zero().bit_not().shl_bits(from)).trailing_zeros() as usize;
   if x == T::NUM_BITS {
     None
   } else {
     Some(x)
   }
-}
\ No newline at end of file
+}
+
+/// An iterator over maximal runs of consecutive bits of a `BitSet`, producing
+/// `Range<usize>`s.
+///
+/// This struct is created by the [`runs`] and [`runs_of_zeros`] methods on
+/// [`BitSet`]s.
+///
+/// [`BitSet`]: struct.BitSet.html
+/// [`runs`]: struct.BitSet.html#method.runs
+/// [`runs_of_zeros`]: struct.BitSet.html#method.runs_of_zeros
+pub struct Runs<'a, T> {
+  slice: &'a [T],
+  front: usize,
+  back: usize,
+  zeros: bool,
+}
+
+impl<'a, T> Runs<'a, T>
+where
+  T: BitBlock
+{
+  pub(crate) fn new(set: &'a BitSet<T>) -> Self {
+    Self {
+      slice: &set.vec,
+      front: 0,
+      back: set.num_bits,
+      zeros: false,
+    }
+  }
+
+  pub(crate) fn new_zeros(set: &'a BitSet<T>, within: Range<usize>) -> Self {
+    Self {
+      slice: &set.vec,
+      front: within.start,
+      back: within.end.max(within.start),
+      zeros: true,
+    }
+  }
+
+  /// Returns the `i`-th block, in which the bits belonging to a run are ones
+  /// if `inverted` is false, and zeros otherwise.
+  #[inline(always)]
+  fn block(&self, i: usize, inverted: bool) -> T {
+    let blk = self.slice.get(i).copied().unwrap_or_else(T::zero);
+    if self.zeros != inverted {
+      blk.bit_not()
+    } else {
+      blk
+    }
+  }
+
+  /// Finds the lowest index in `from..end` of a bit belonging to a run (or not
+  /// belonging to a run if `inverted` is true).
+  fn find_next(&self, from: usize, end: usize, inverted: bool) -> Option<usize> {
+    let mut index = from / T::NUM_BITS;
+    let mut blk = self.block(index, inverted)
+      .bit_and(T::zero().bit_not().shl_bits(from % T::NUM_BITS));
+
+    while index * T::NUM_BITS < end {
+      if blk != T::zero() {
+        let x = index * T::NUM_BITS + blk.trailing_zeros() as usize;
+        return if x < end { Some(x) } else { None };
+      }
+      if index >= self.slice.len() {
+        // All the blocks past the end of the slice are the same.
+        return None;
+      }
+      index += 1;
+      blk = self.block(index, inverted);
+    }
+    None
+  }
+
+  /// Finds the highest index in `start..end` of a bit belonging to a run (or
+  /// not belonging to a run if `inverted` is true).
+  fn find_prev(&self, start: usize, end: usize, inverted: bool) -> Option<usize> {
+    if end <= start {
+      return None;
+    }
+
+    let last = end - 1;
+    let mut index = last / T::NUM_BITS;
+    let mut blk = self.block(index, inverted)
+      .bit_and(T::zero().bit_not().shr_bits(T::NUM_BITS - 1 - last % T::NUM_BITS));
+
+    loop {
+      if blk != T::zero() {
+        let x = (index + 1) * T::NUM_BITS - 1 - blk.leading_zeros() as usize;
+        return if x >= start { Some(x) } else { None };
+      }
+      // All the blocks past the end of the slice are the same.
+      index = index.min(self.slice.len());
+      if index * T::NUM_BITS <= start {
+        return None;
+      }
+      index -= 1;
+      blk = self.block(index, inverted);
+    }
+  }
+}
+
+impl<'a, T> Iterator for Runs<'a, T>
+where
+  T: BitBlock,
+{
+  type Item = Range<usize>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let start = self.find_next(self.front, self.back, false)?;
+    let end = self.find_next(start, self.back, true).unwrap_or(self.back);
+    self.front = end;
+    Some(start..end)
+  }
+}
+
+impl<'a, T> DoubleEndedIterator for Runs<'a, T>
+where
+  T: BitBlock,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let last = self.find_prev(self.front, self.back, false)?;
+    let start = self.find_prev(self.front, last, true)
+      .map_or(self.front, |x| x + 1);
+    self.back = start;
+    Some(start..last + 1)
+  }
+}
+
+/// An iterator over the non-zero blocks of a `BitSet`, producing `(usize, T)`s.
+///
+/// This struct is created by the [`iter_blocks`] method on [`BitSet`]s.
+///
+/// [`BitSet`]: struct.BitSet.html
+/// [`iter_blocks`]: struct.BitSet.html#method.iter_blocks
+pub struct Blocks<'a, T> {
+  iter: Enumerate<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Blocks<'a, T>
+where
+  T: BitBlock
+{
+  pub(crate) fn new(slice: &'a [T]) -> Self {
+    Self {
+      iter: slice.iter().enumerate(),
+    }
+  }
+}
+
+impl<'a, T> Iterator for Blocks<'a, T>
+where
+  T: BitBlock,
+{
+  type Item = (usize, T);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.iter.by_ref()
+      .find(|(_, &blk)| blk != T::zero())
+      .map(|(i, &blk)| (i * T::NUM_BITS, blk))
+  }
+}
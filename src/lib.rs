@@ -38,14 +38,65 @@
 //!   println!("{}", x);
 //! }
 //! ```
-
-pub use self::iter::Iter;
-pub use self::block::BitBlock;
+//!
+//! # Optional features
+//!
+//! - `rayon`: parallel iteration and parallel set operations for `BitSet`.
+//! - `derive`: `#[derive(BitSetKey)]` for the enums used as the elements of
+//!   `EnumSet`.
+
+pub use self::iter::{Iter, Runs, Blocks};
+pub use self::block::{BitBlock, PlainBlock, BlocksMut};
+pub use self::view::{BitSetRef, BitSetMut};
+#[cfg(unix)]
+pub use self::mmap::{MmapBitSet, ReadOnlyMmapBitSet};
+pub use self::stream::{BlockReader, BlockWriter, MergeBlocks, MergeElements};
+pub use self::atomic::{AtomicBitBlock, AtomicBitSet, AtomicIter};
+pub use self::concurrent::ConcurrentBitSet;
+pub use self::expr::{Expr, ExprIter};
+pub use self::typed::{BitIndex, TypedBitSet, TypedIter};
+pub use self::enum_set::{BitSetKey, EnumSet, EnumSetIter};
+pub use self::bounded::{BoundedBitSet, IterAbsent, OutOfRange};
+pub use self::cofinite::CofiniteBitSet;
+pub use self::offset::{OffsetBitSet, OffsetIter, OffsetKey};
+pub use self::window::{Mark, Missing, SlidingWindow, TooOld};
+pub use self::tracked::{IterInserted, TrackedBitSet};
+pub use self::id_alloc::IdAllocator;
+pub use self::range_alloc::{Fit, FreeSpaceStats, RangeAllocator};
+pub use self::counting::{CountingBitSet, Counts};
+#[cfg(feature = "derive")]
+pub use bittyset_derive::BitSetKey;
+#[cfg(feature = "rayon")]
+pub use self::par::ParIter;
+
+use std::ops::Range;
 
 mod iter;
 mod block;
 mod impls;
 mod macros;
+mod simd;
+mod view;
+mod format;
+mod stream;
+mod atomic;
+mod concurrent;
+mod count;
+mod expr;
+mod typed;
+mod enum_set;
+mod bounded;
+mod cofinite;
+mod offset;
+mod window;
+mod tracked;
+mod id_alloc;
+mod range_alloc;
+mod counting;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(unix)]
+mod mmap;
 
 /// A `BitSet` type based on bit vectors.
 ///
@@ -54,14 +105,16 @@ mod macros;
 pub struct BitSet<T = usize> {
   /// # Invariants
   ///
-  /// If `num_bits` is not a multiple of `T::NUM_BITS`, the highest
-  /// `T::NUM_BITS - num_bits % T::NUM_BITS` bits of the last block of the set
-  /// are all zeros.
+  /// The length of `vec` is the number of blocks needed for `num_bits` bits,
+  /// and the bits indexed by `num_bits` and above are all zeros.
   ///
-  /// the bit indexed by `num_bits - 1` is always set.
+  /// The bit indexed by `num_bits - 1` may be unset, and there may be trailing
+  /// zero blocks, which are trimmed lazily: the set operations may leave a
+  /// zero tail, which the next successful `remove` or `compact` drops, so
+  /// that each block is dropped once.
   vec: Vec<T>,
 
-  /// Number of all bits (set & unset).
+  /// Number of all bits (set & unset), an upper bound of the elements.
   num_bits: usize,
 }
 
@@ -119,7 +172,7 @@ where
   /// assert_eq!(set.capacity(), 16);
   /// ```
   pub fn capacity(&self) -> usize {
-    self.vec.capacity().checked_mul(T::NUM_BITS).unwrap_or(std::usize::MAX)
+    self.vec.capacity().saturating_mul(T::NUM_BITS)
   }
 
   /// Reserves capacity for at least `additional` more bits for the underlying bit
@@ -176,6 +229,7 @@ where
   /// assert!(set.capacity() >= 8);
   /// ```
   pub fn shrink_to_fit(&mut self) {
+    self.compact();
     self.vec.shrink_to_fit();
   }
 
@@ -192,6 +246,173 @@ where
     self.num_bits = 0;
   }
 
+  /// Creates a `BitSet` from a bit vector which may have trailing zero blocks.
+  pub(crate) fn from_vec(vec: Vec<T>) -> Self {
+    let mut set = Self { vec, num_bits: 0 };
+    set.compact();
+    set
+  }
+
+  /// Creates a `BitSet` from the blocks of its underlying bit vector. Trailing
+  /// zero blocks are removed.
+  ///
+  /// The bit indexed by `i` is stored in the block `i / T::NUM_BITS` at the
+  /// bit position `i % T::NUM_BITS`.
+  ///
+  /// Returns `None` if the number of bits of `blocks` exceeds `usize::MAX`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::BitSet;
+  ///
+  /// let set = BitSet::from_blocks(vec![0b101u8, 0b10, 0, 0]).unwrap();
+  ///
+  /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 2, 9]);
+  /// assert_eq!(set.as_blocks(), &[0b101, 0b10]);
+  /// ```
+  pub fn from_blocks(blocks: Vec<T>) -> Option<Self> {
+    blocks.len().checked_mul(T::NUM_BITS)?;
+    Some(Self::from_vec(blocks))
+  }
+
+  /// Returns the blocks of the underlying bit vector. The last block, if any,
+  /// is never zero.
+  ///
+  /// The trailing zero blocks left by the set operations are skipped on every
+  /// call until they are dropped, which takes time proportional to their
+  /// number.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set = bitset![0, 65];
+  ///
+  /// assert_eq!(set.as_blocks(), &[1, 2]);
+  /// ```
+  pub fn as_blocks(&self) -> &[T] {
+    trim_zero_blocks(&self.vec)
+  }
+
+  /// Drops the trailing zero blocks, in time proportional to their number.
+  fn trim_trailing_zero_blocks(&mut self) {
+    let nblks = trim_zero_blocks(&self.vec).len();
+    if nblks < self.vec.len() {
+      self.vec.truncate(nblks);
+      self.num_bits = nblks * T::NUM_BITS;
+    }
+  }
+
+  /// Returns one more than the largest element, or zero if the set is empty.
+  pub(crate) fn end(&self) -> usize {
+    let blocks = self.as_blocks();
+    match blocks.last() {
+      Some(blk) => blocks.len() * T::NUM_BITS - blk.leading_zeros() as usize,
+      None => 0,
+    }
+  }
+
+  /// Shifts the elements up by `nblks` blocks.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of bits exceeds `usize::MAX`.
+  pub(crate) fn prepend_zero_blocks(&mut self, nblks: usize) {
+    if nblks == 0 || self.vec.is_empty() {
+      return;
+    }
+    self.num_bits = nblks.checked_mul(T::NUM_BITS)
+      .and_then(|n| n.checked_add(self.num_bits))
+      .expect("capacity overflow");
+    self.vec.splice(0..0, std::iter::repeat_n(T::zero(), nblks));
+  }
+
+  /// Zeroes the blocks holding `values`, keeping the length of the underlying
+  /// bit vector so that it is not zeroed again when the values are reinserted.
+  pub(crate) fn clear_blocks_of<I>(&mut self, values: I)
+  where
+    I: IntoIterator<Item = usize>,
+  {
+    for value in values {
+      if let Some(blk) = self.vec.get_mut(value / T::NUM_BITS) {
+        *blk = T::zero();
+      }
+    }
+  }
+
+  /// Removes `value` without dropping the trailing zero blocks. Returns
+  /// whether the value was present in the set.
+  pub(crate) fn remove_in_place(&mut self, value: usize) -> bool {
+    if value >= self.num_bits {
+      return false;
+    }
+
+    let present = self.contains_unchecked(value);
+    let blk = &mut self.vec[value / T::NUM_BITS];
+    *blk = blk.bit_and(T::one().shl_bits(value % T::NUM_BITS).bit_not());
+    present
+  }
+
+  /// Converts the set into the blocks of its underlying bit vector. The last
+  /// block, if any, is never zero.
+  pub fn into_blocks(mut self) -> Vec<T> {
+    self.compact();
+    self.vec
+  }
+
+  /// Returns a guard giving mutable access to the blocks of the underlying bit
+  /// vector. The invariants of the set are restored when the guard is dropped.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let mut set = bitset![0, 65];
+  /// set.blocks_mut()[1] = 0;
+  ///
+  /// assert_eq!(set, bitset![0]);
+  /// ```
+  pub fn blocks_mut(&mut self) -> BlocksMut<'_, T> {
+    BlocksMut::new(self)
+  }
+
+  /// Iterates over the non-zero blocks of the underlying bit vector, producing
+  /// `(usize, T)`s of the index of the lowest bit of the block and the block.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::BitSet;
+  ///
+  /// let set = [1, 2, 40].iter().cloned().collect::<BitSet<u8>>();
+  /// let blocks = set.iter_blocks().collect::<Vec<_>>();
+  ///
+  /// assert_eq!(blocks, vec![(0, 0b110), (40, 0b1)]);
+  /// ```
+  pub fn iter_blocks(&self) -> Blocks<'_, T> {
+    Blocks::new(self.as_blocks())
+  }
+
+  /// Returns a read-only view of the set.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let view = set1.as_view();
+  ///
+  /// assert!(view.contains(18));
+  /// assert_eq!(view.len(), 4);
+  /// ```
+  pub fn as_view(&self) -> BitSetRef<'_, T> {
+    BitSetRef::new(&self.vec)
+  }
+
   /// Iterates over the `BitSet`, producing `usize`s representing the elements
   /// in the set, in ascending order.
   ///
@@ -205,7 +426,7 @@ where
   ///
   /// assert_eq!(vec1, vec![3,5,7,18]);
   /// ```
-  pub fn iter(&self) -> Iter<T> {
+  pub fn iter(&self) -> Iter<'_, T> {
     Iter::new(self)
   }
 
@@ -222,13 +443,152 @@ where
   ///
   /// assert_eq!(vec1, vec![15,18]);
   /// ```
-  pub fn iter_from(&self, start: usize) -> Iter<T> {
+  pub fn iter_from(&self, start: usize) -> Iter<'_, T> {
     Iter::new_from(self, start)
   }
 
+  /// Iterates over the maximal runs of consecutive elements in the set,
+  /// producing `Range<usize>`s in ascending order.
+  ///
+  /// The returned iterator is double-ended, so the runs can also be visited in
+  /// descending order.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set1 = bitset![1,2,3,7,9,10];
+  /// let runs = set1.runs().collect::<Vec<_>>();
+  ///
+  /// assert_eq!(runs, vec![1..4, 7..8, 9..11]);
+  /// assert_eq!(set1.runs().next_back(), Some(9..11));
+  /// ```
+  pub fn runs(&self) -> Runs<'_, T> {
+    Runs::new(self)
+  }
+
+  /// Iterates over the maximal runs of consecutive values absent from the set
+  /// within the range `within`, producing `Range<usize>`s in ascending order.
+  ///
+  /// The returned iterator is double-ended, so the runs can also be visited in
+  /// descending order.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set1 = bitset![1,2,3,7,9,10];
+  /// let gaps = set1.runs_of_zeros(0..15).collect::<Vec<_>>();
+  ///
+  /// assert_eq!(gaps, vec![0..1, 4..7, 8..9, 11..15]);
+  /// ```
+  pub fn runs_of_zeros(&self, within: Range<usize>) -> Runs<'_, T> {
+    Runs::new_zeros(self, within)
+  }
+
+  /// Creates a `BitSet` containing all the values in the given ranges.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let set1 = <BitSet>::from_ranges(vec![1..4, 7..8, 9..11]);
+  ///
+  /// assert_eq!(set1, bitset![1,2,3,7,9,10]);
+  /// ```
+  pub fn from_ranges<I>(ranges: I) -> Self
+  where
+    I: IntoIterator<Item = Range<usize>>,
+  {
+    let mut set = Self::new();
+    set.extend_ranges(ranges);
+    set
+  }
+
+  /// Adds all the values in the given ranges to the set.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let mut set1 = bitset![0,5];
+  /// set1.extend_ranges(vec![1..3, 10..12]);
+  ///
+  /// assert_eq!(set1, bitset![0,1,2,5,10,11]);
+  /// ```
+  pub fn extend_ranges<I>(&mut self, ranges: I)
+  where
+    I: IntoIterator<Item = Range<usize>>,
+  {
+    for range in ranges {
+      self.insert_range(range);
+    }
+  }
+
+  fn insert_range(&mut self, range: Range<usize>) {
+    if range.start >= range.end {
+      return;
+    }
+
+    let nblks = compute_num_blocks::<T>(range.end);
+    if self.vec.len() < nblks {
+      self.vec.resize(nblks, T::zero());
+    }
+
+    if self.num_bits < range.end {
+      self.num_bits = range.end;
+    }
+
+    let first = range.start / T::NUM_BITS;
+    let last = (range.end - 1) / T::NUM_BITS;
+    for i in first..=last {
+      self.vec[i] = self.vec[i].bit_or(range_mask::<T>(i, &range));
+    }
+  }
+
+  /// Removes all the values in the given ranges from the set.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let mut set1 = bitset![0,1,2,5,10,11];
+  /// set1.remove_ranges(vec![1..3, 9..20]);
+  ///
+  /// assert_eq!(set1, bitset![0,5]);
+  /// ```
+  pub fn remove_ranges<I>(&mut self, ranges: I)
+  where
+    I: IntoIterator<Item = Range<usize>>,
+  {
+    for range in ranges {
+      self.remove_range(range);
+    }
+  }
+
+  fn remove_range(&mut self, range: Range<usize>) {
+    let end = range.end.min(self.num_bits);
+    if range.start >= end {
+      return;
+    }
+
+    let range = range.start..end;
+    let first = range.start / T::NUM_BITS;
+    let last = (range.end - 1) / T::NUM_BITS;
+    for i in first..=last {
+      self.vec[i] = self.vec[i].bit_and(range_mask::<T>(i, &range).bit_not());
+    }
+    self.trim_trailing_zero_blocks();
+  }
+
   /// Returns the number of elements in the set.
   pub fn len(&self) -> usize {
-    self.vec.iter().map(|x| x.count_ones() as usize).sum()
+    simd::count_ones(&self.vec)
   }
 
   /// Returns whether the set is empty.
@@ -267,7 +627,7 @@ where
 
   #[inline(always)]
   fn contains_unchecked(&self, value: usize) -> bool {
-    self.vec[value / T::NUM_BITS] & (T::one() << (value % T::NUM_BITS)) != T::zero()
+    self.vec[value / T::NUM_BITS].bit_and(T::one().shl_bits(value % T::NUM_BITS)) != T::zero()
   }
 
   /// Adds a value to the set.
@@ -297,7 +657,8 @@ where
     }
 
     let present = self.contains_unchecked(value);
-    self.vec[value / T::NUM_BITS] |= T::one() << (value % T::NUM_BITS);
+    let blk = &mut self.vec[value / T::NUM_BITS];
+    *blk = blk.bit_or(T::one().shl_bits(value % T::NUM_BITS));
     !present
   }
 
@@ -314,18 +675,11 @@ where
   /// assert!(!set1.remove(13));
   /// ```
   pub fn remove(&mut self, value: usize) -> bool {
-    if value >= self.num_bits {
+    if !self.remove_in_place(value) {
       return false;
     }
-
-    let present = self.contains_unchecked(value);
-    self.vec[value / T::NUM_BITS] &= !(T::one() << (value % T::NUM_BITS));
-
-    if present && value + 1 == self.num_bits {
-      self.compact();
-    }
-
-    present
+    self.trim_trailing_zero_blocks();
+    true
   }
 
   /// Computes the union of the set and `other`.
@@ -492,6 +846,278 @@ where
     *self ^= other;
   }
 
+  /// Replaces the contents of the set with the contents of `other`.
+  ///
+  /// Unlike `clone`, the allocation of the underlying bit vector is reused,
+  /// so no reallocation happens if the capacity suffices.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let mut set1 = BitSet::with_capacity(100);
+  /// let set2 = bitset![3,1,6,7,24];
+  ///
+  /// set1.assign_from(&set2);
+  ///
+  /// assert_eq!(set1, set2);
+  /// assert!(set1.capacity() >= 100);
+  /// ```
+  pub fn assign_from(&mut self, other: &Self) {
+    self.vec.clear();
+    self.vec.extend_from_slice(&other.vec);
+    self.num_bits = other.num_bits;
+  }
+
+  /// Computes the union of `a` and `b` into `out`, reusing the allocation of
+  /// `out`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  /// let mut out = BitSet::new();
+  ///
+  /// BitSet::union_into(&set1, &set2, &mut out);
+  ///
+  /// assert_eq!(out, bitset![1,3,5,6,7,18,24]);
+  /// ```
+  pub fn union_into(a: &Self, b: &Self, out: &mut Self) {
+    let (short, long) = if a.num_bits < b.num_bits { (a, b) } else { (b, a) };
+    out.assign_from(long);
+    *out |= short;
+  }
+
+  /// Computes the intersection of `a` and `b` into `out`, reusing the
+  /// allocation of `out`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  /// let mut out = BitSet::new();
+  ///
+  /// BitSet::intersection_into(&set1, &set2, &mut out);
+  ///
+  /// assert_eq!(out, bitset![3,7]);
+  /// ```
+  pub fn intersection_into(a: &Self, b: &Self, out: &mut Self) {
+    let (short, long) = if a.num_bits < b.num_bits { (a, b) } else { (b, a) };
+    out.assign_from(short);
+    *out &= long;
+  }
+
+  /// Computes the difference of `a` and `b` into `out`, reusing the
+  /// allocation of `out`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  /// let mut out = BitSet::new();
+  ///
+  /// BitSet::difference_into(&set1, &set2, &mut out);
+  ///
+  /// assert_eq!(out, bitset![5,18]);
+  /// ```
+  pub fn difference_into(a: &Self, b: &Self, out: &mut Self) {
+    out.assign_from(a);
+    *out -= b;
+  }
+
+  /// Computes the symmetric difference of `a` and `b` into `out`, reusing the
+  /// allocation of `out`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  /// let mut out = BitSet::new();
+  ///
+  /// BitSet::symmetric_difference_into(&set1, &set2, &mut out);
+  ///
+  /// assert_eq!(out, bitset![1,5,6,18,24]);
+  /// ```
+  pub fn symmetric_difference_into(a: &Self, b: &Self, out: &mut Self) {
+    let (short, long) = if a.num_bits < b.num_bits { (a, b) } else { (b, a) };
+    out.assign_from(long);
+    *out ^= short;
+  }
+
+  /// Computes the union of all the `sets`.
+  ///
+  /// The block index `i` of all the sets is combined in one pass, without
+  /// intermediate sets.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let sets = vec![bitset![7,3,5,18], bitset![3,1,6,7,24], bitset![2,100]];
+  ///
+  /// assert_eq!(BitSet::union_all(&sets), bitset![1,2,3,5,6,7,18,24,100]);
+  /// ```
+  pub fn union_all<'a, I>(sets: I) -> Self
+  where
+    I: IntoIterator<Item = &'a Self>,
+    T: 'a,
+  {
+    let sets = sets.into_iter().map(|set| &set.vec[..]).collect::<Vec<_>>();
+    let len = sets.iter().map(|blks| blks.len()).max().unwrap_or(0);
+    let mut vec = vec![T::zero(); len];
+    fold_chunks::<T, simd::Or>(&mut vec, &sets);
+    Self::from_vec(vec)
+  }
+
+  /// Computes the intersection of all the `sets`.
+  ///
+  /// The block index `i` of all the sets is combined in one pass, without
+  /// intermediate sets. Returns an empty set if `sets` is empty.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let sets = vec![bitset![7,3,5,18], bitset![3,1,6,7,24], bitset![3,7,100]];
+  ///
+  /// assert_eq!(BitSet::intersection_all(&sets), bitset![3,7]);
+  /// ```
+  pub fn intersection_all<'a, I>(sets: I) -> Self
+  where
+    I: IntoIterator<Item = &'a Self>,
+    T: 'a,
+  {
+    let sets = sets.into_iter().map(|set| &set.vec[..]).collect::<Vec<_>>();
+    let (first, rest) = match sets.iter().enumerate().min_by_key(|(_, blks)| blks.len()) {
+      Some((i, &first)) => (first, [&sets[..i], &sets[i + 1..]].concat()),
+      None => return Self::new(),
+    };
+    let mut vec = first.to_vec();
+    fold_chunks::<T, simd::And>(&mut vec, &rest);
+    Self::from_vec(vec)
+  }
+
+  /// Computes the difference of `base` and the union of all the `sets`.
+  ///
+  /// The block index `i` of all the sets is combined in one pass, without
+  /// intermediate sets.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let base = bitset![1,2,3,5,6,7,18,24,100];
+  /// let sets = vec![bitset![7,3,5,18], bitset![3,1,6,7,24]];
+  ///
+  /// assert_eq!(BitSet::difference_of(&base, &sets), bitset![2,100]);
+  /// ```
+  pub fn difference_of<'a, I>(base: &Self, sets: I) -> Self
+  where
+    I: IntoIterator<Item = &'a Self>,
+    T: 'a,
+  {
+    let sets = sets.into_iter().map(|set| &set.vec[..]).collect::<Vec<_>>();
+    let mut vec = base.vec.clone();
+    fold_chunks::<T, simd::AndNot>(&mut vec, &sets);
+    Self::from_vec(vec)
+  }
+
+  /// Returns the number of elements in the union of the set and `other`,
+  /// without computing the union.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  ///
+  /// assert_eq!(set1.union_len(&set2), 7);
+  /// ```
+  pub fn union_len(&self, other: &Self) -> usize {
+    let (short, long) = if self.vec.len() < other.vec.len() {
+      (&self.vec, &other.vec)
+    } else {
+      (&other.vec, &self.vec)
+    };
+    simd::count_ones_op::<T, simd::Or>(short, long) + simd::count_ones(&long[short.len()..])
+  }
+
+  /// Returns the number of elements in the intersection of the set and
+  /// `other`, without computing the intersection.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  ///
+  /// assert_eq!(set1.intersection_len(&set2), 2);
+  /// ```
+  pub fn intersection_len(&self, other: &Self) -> usize {
+    simd::count_ones_op::<T, simd::And>(&self.vec, &other.vec)
+  }
+
+  /// Returns the number of elements in the difference of the set and `other`,
+  /// without computing the difference.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  ///
+  /// assert_eq!(set1.difference_len(&set2), 2);
+  /// ```
+  pub fn difference_len(&self, other: &Self) -> usize {
+    let common = self.vec.len().min(other.vec.len());
+    simd::count_ones_op::<T, simd::AndNot>(&self.vec, &other.vec) +
+      simd::count_ones(&self.vec[common..])
+  }
+
+  /// Returns the number of elements in the symmetric difference of the set
+  /// and `other`, without computing the symmetric difference.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::bitset;
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let set2 = bitset![3,1,6,7,24];
+  ///
+  /// assert_eq!(set1.symmetric_difference_len(&set2), 5);
+  /// ```
+  pub fn symmetric_difference_len(&self, other: &Self) -> usize {
+    let (short, long) = if self.vec.len() < other.vec.len() {
+      (&self.vec, &other.vec)
+    } else {
+      (&other.vec, &self.vec)
+    };
+    simd::count_ones_op::<T, simd::Xor>(short, long) + simd::count_ones(&long[short.len()..])
+  }
+
   /// Returns whether the set is a subset of `other`.
   ///
   /// # Examples
@@ -506,18 +1132,7 @@ where
   /// assert!(!set2.is_subset(&set1));
   /// ```
   pub fn is_subset(&self, other: &Self) -> bool {
-    if self.num_bits > other.num_bits {
-      return false;
-    }
-
-    let nblks = crate::compute_num_blocks::<T>(self.num_bits);
-    for i in 0..nblks {
-      if self.vec[i] & !other.vec[i] != T::zero() {
-        return false;
-      }
-    }
-
-    true
+    simd::is_subset(&self.vec, &other.vec)
   }
 
   /// Returns whether the set is a proper subset of `other`.
@@ -535,31 +1150,48 @@ where
   /// assert!(!set1.is_proper_subset(&set1));
   /// ```
   pub fn is_proper_subset(&self, other: &Self) -> bool {
-    if self.num_bits > other.num_bits {
-      return false;
-    }
-
-    let nblks1 = crate::compute_num_blocks::<T>(self.num_bits);
-    let nblks2 = crate::compute_num_blocks::<T>(other.num_bits);
-    let mut equal = nblks1 == nblks2;
+    self.is_subset(other) && self != other
+  }
+}
 
-    for i in 0..nblks1 {
-      if self.vec[i] & !other.vec[i] != T::zero() {
-        return false;
-      }
+#[inline(always)]
+fn compute_num_blocks<T: BitBlock>(num_bits: usize) -> usize {
+  num_bits.div_ceil(T::NUM_BITS)
+}
 
-      if self.vec[i] != other.vec[i] {
-        equal = false;
-      }
-    }
+/// Returns the blocks up to the last non-zero one.
+#[inline(always)]
+pub(crate) fn trim_zero_blocks<T: BitBlock>(blocks: &[T]) -> &[T] {
+  let nblks = blocks.iter().rposition(|&blk| blk != T::zero()).map_or(0, |i| i + 1);
+  &blocks[..nblks]
+}
 
-    !equal
+/// Returns the mask of the bits of the block `i` within the non-empty `range`.
+#[inline(always)]
+fn range_mask<T: BitBlock>(i: usize, range: &Range<usize>) -> T {
+  let mut mask = T::zero().bit_not();
+  if i == range.start / T::NUM_BITS {
+    mask = mask.bit_and(T::zero().bit_not().shl_bits(range.start % T::NUM_BITS));
+  }
+  if i == (range.end - 1) / T::NUM_BITS {
+    let last = (range.end - 1) % T::NUM_BITS;
+    mask = mask.bit_and(T::zero().bit_not().shr_bits(T::NUM_BITS - 1 - last));
   }
+  mask
 }
 
-#[inline(always)]
-fn compute_num_blocks<T: BitBlock>(num_bits: usize) -> usize {
-  (num_bits + T::NUM_BITS - 1) / T::NUM_BITS
+/// Applies `O` to `dst` and each of `srcs` in turn. The blocks of `dst` past
+/// the end of a source are left as is. `dst` is processed in chunks which stay
+/// in the cache while all of `srcs` are applied to them.
+fn fold_chunks<T: BitBlock, O: simd::BinOp>(dst: &mut [T], srcs: &[&[T]]) {
+  let chunk = (4096 / std::mem::size_of::<T>()).max(1);
+
+  for start in (0..dst.len()).step_by(chunk) {
+    let end = dst.len().min(start + chunk);
+    for src in srcs.iter().filter(|src| src.len() > start) {
+      simd::apply_assign::<T, O>(&mut dst[start..end], &src[start..]);
+    }
+  }
 }
 
 #[cfg(test)]
@@ -726,4 +1358,48 @@ mod tests {
     set.reserve_exact(110);
   }
 
+  #[test]
+  fn blocks_mut() {
+    let mut set = BitSet::<u8>::new();
+    set.insert(3);
+    set.insert(20);
+
+    set.blocks_mut()[1] = 0xff;
+    assert_eq!(set.num_bits, 21);
+
+    set.blocks_mut()[2] = 0;
+    assert_eq!(set.num_bits, 16);
+    assert_eq!(set.vec.len(), 2);
+
+    set.blocks_mut().fill(0);
+    assert_eq!(set.num_bits, 0);
+    assert!(set.vec.is_empty());
+  }
+
+  #[test]
+  fn lazy_trim() {
+    let set1 = (0..1000).collect::<BitSet<u16>>();
+    let set2 = [3, 30].iter().cloned().collect::<BitSet<u16>>();
+    let mut set3 = &set1 & &(0..1000).step_by(10).collect::<BitSet<u16>>();
+    set3 -= &(40..1000).collect::<BitSet<u16>>();
+    set3.insert(3);
+
+    assert_eq!(set3.vec.len(), 62);
+    assert_eq!(set3, [0, 3, 10, 20, 30].iter().cloned().collect::<BitSet<u16>>());
+    assert_eq!(set3.as_blocks().len(), 2);
+    assert!(set2.is_subset(&set3));
+    assert!(set3.is_subset(&set1));
+    assert!(!set3.is_subset(&set2));
+
+    set3.remove(10);
+    assert_eq!(set3.vec.len(), 2);
+    assert_eq!(set3.num_bits, 32);
+
+    for x in (0..1000).rev() {
+      set3.remove(x);
+    }
+    assert!(set3.vec.is_empty());
+    assert_eq!(set3.num_bits, 0);
+  }
+
 }
\ No newline at end of file
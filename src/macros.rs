@@ -40,4 +40,37 @@ macro_rules! bitset {
     )*
     set
   }};
-}
\ No newline at end of file
+}
+
+/// Creates an `EnumSet` containing the arguments, which are variants of the
+/// same enum deriving `BitSetKey`. The macro can be used in constant
+/// expressions.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use bittyset::{BitSetKey, EnumSet, enum_set};
+///
+/// #[derive(Clone, Copy, Debug, BitSetKey)]
+/// enum Weekday {
+///   Mon, Tue, Wed, Thu, Fri, Sat, Sun,
+/// }
+///
+/// const WEEKEND: EnumSet<Weekday> = enum_set![Weekday::Sat, Weekday::Sun];
+/// const NONE: EnumSet<Weekday> = enum_set![];
+///
+/// assert_eq!(WEEKEND.len(), 2);
+/// assert_eq!((!WEEKEND).len(), 5);
+/// assert!(NONE.is_empty());
+/// ```
+#[macro_export]
+macro_rules! enum_set {
+  () => {
+    $crate::EnumSet::empty()
+  };
+  ($t:ident :: $v:ident $(, $ts:ident :: $vs:ident)* $(,)?) => {
+    $crate::EnumSet::<$t>::from_block_unchecked(
+      <<$t as $crate::BitSetKey>::ConstBlock>::block(&[$t::$v $(, $ts::$vs)*]))
+  };
+}
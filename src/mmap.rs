@@ -0,0 +1,397 @@
+use std::fmt::{self, Debug, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use crate::{BitSet, BitSetRef, Iter, PlainBlock};
+use crate::format::{Header, HEADER_LEN};
+
+/// Minimum number of blocks allocated when the file grows.
+const MIN_GROW_BLOCKS: usize = 64;
+
+/// A bit set backed by a memory-mapped file.
+///
+/// The file consists of a header followed by the blocks of the set, in the
+/// same layout as `BitSet` and in native byte order. Opening a file only maps
+/// it, so the blocks are paged in lazily as they are accessed.
+///
+/// Modifications are written to the mapping directly, and are made durable by
+/// [`flush`]. Inserting a value beyond the end of the file grows the file and
+/// remaps it.
+///
+/// The file must not be modified by others while it is mapped. Files without
+/// write access can be opened for queries with [`open_read_only`].
+///
+/// [`flush`]: struct.MmapBitSet.html#method.flush
+/// [`open_read_only`]: struct.MmapBitSet.html#method.open_read_only
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::MmapBitSet;
+///
+/// let path = std::env::temp_dir().join("bittyset-doc-mmap");
+///
+/// let mut set = MmapBitSet::<u64>::create(&path)?;
+/// set.insert(3)?;
+/// set.insert(1_000_000)?;
+/// set.flush()?;
+/// drop(set);
+///
+/// let set = MmapBitSet::<u64>::open(&path)?;
+/// assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 1_000_000]);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct MmapBitSet<T = usize> {
+  file: File,
+  ptr: *mut u8,
+  /// Length of the mapping in bytes, including the header.
+  len: usize,
+  _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for MmapBitSet<T> {}
+unsafe impl<T: Sync> Sync for MmapBitSet<T> {}
+
+impl<T> MmapBitSet<T>
+where
+  T: PlainBlock,
+{
+  /// Creates a new empty set backed by the file at `path`, truncating the file
+  /// if it exists.
+  pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    Self::create_from(path, &BitSet::new())
+  }
+
+  /// Creates a new set with the elements of `set`, backed by the file at
+  /// `path`, truncating the file if it exists.
+  pub fn create_from<P: AsRef<Path>>(path: P, set: &BitSet<T>) -> io::Result<Self> {
+    let file = OpenOptions::new()
+      .read(true)
+      .write(true)
+      .create(true)
+      .truncate(true)
+      .open(path)?;
+    let blocks = set.as_blocks();
+    file.set_len((HEADER_LEN + mem::size_of_val(blocks)) as u64)?;
+
+    let mut mmap = Self::map(file, HEADER_LEN + mem::size_of_val(blocks))?;
+    mmap.write_header(blocks.len());
+    mmap.blocks_mut().copy_from_slice(blocks);
+    Ok(mmap)
+  }
+
+  /// Opens the set backed by the file at `path`.
+  ///
+  /// Returns an error of kind `InvalidData` if the file was not created by
+  /// `MmapBitSet` with the same block type and byte order.
+  pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    let (file, len) = open_file::<T>(path.as_ref(), true)?;
+    Self::map(file, len)
+  }
+
+  /// Opens the set backed by the file at `path` for queries only, which does
+  /// not require write access to the file.
+  ///
+  /// Returns an error of kind `InvalidData` if the file was not created by
+  /// `MmapBitSet` with the same block type and byte order.
+  pub fn open_read_only<P: AsRef<Path>>(path: P) -> io::Result<ReadOnlyMmapBitSet<T>> {
+    let (file, len) = open_file::<T>(path.as_ref(), false)?;
+    let ptr = map(&file, len, libc::PROT_READ)?;
+    Ok(ReadOnlyMmapBitSet {
+      ptr,
+      len,
+      _marker: PhantomData,
+    })
+  }
+
+  fn map(file: File, len: usize) -> io::Result<Self> {
+    let ptr = map(&file, len, libc::PROT_READ | libc::PROT_WRITE)?;
+    Ok(Self {
+      file,
+      ptr,
+      len,
+      _marker: PhantomData,
+    })
+  }
+
+  fn write_header(&mut self, num_blocks: usize) {
+    // SAFETY: the mapping is at least `HEADER_LEN` bytes long and page
+    // aligned.
+    unsafe {
+      ptr::write(self.ptr as *mut Header, Header::new::<T>(num_blocks));
+    }
+  }
+
+  fn num_blocks(&self) -> usize {
+    (self.len - HEADER_LEN) / mem::size_of::<T>()
+  }
+
+  fn blocks(&self) -> &[T] {
+    // SAFETY: `ptr` and `len` describe the current mapping.
+    unsafe { blocks(self.ptr, self.len) }
+  }
+
+  fn blocks_mut(&mut self) -> &mut [T] {
+    // SAFETY: see `blocks`. The mapping is writable.
+    unsafe {
+      slice::from_raw_parts_mut(self.ptr.add(HEADER_LEN) as *mut T, self.num_blocks())
+    }
+  }
+
+  /// Grows the file so that it holds at least `num_blocks` blocks, and remaps
+  /// it.
+  fn grow(&mut self, num_blocks: usize) -> io::Result<()> {
+    let num_blocks = num_blocks
+      .max(self.num_blocks() * 2)
+      .max(MIN_GROW_BLOCKS);
+    let len = HEADER_LEN + num_blocks * mem::size_of::<T>();
+    let file = self.file.try_clone()?;
+    file.set_len(len as u64)?;
+
+    // Write the header only once the new mapping is in place, and restore the
+    // old length if mapping fails, so that the file stays consistent.
+    match Self::map(file, len) {
+      Ok(map) => {
+        *self = map;
+        self.write_header(num_blocks);
+        Ok(())
+      }
+      Err(err) => {
+        let _ = self.file.set_len(self.len as u64);
+        Err(err)
+      }
+    }
+  }
+
+  /// Returns a read-only view of the set.
+  pub fn as_view(&self) -> BitSetRef<'_, T> {
+    BitSetRef::new(self.blocks())
+  }
+
+  /// Iterates over the set, producing `usize`s representing the elements in
+  /// the set, in ascending order.
+  pub fn iter(&self) -> Iter<'_, T> {
+    self.as_view().iter()
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.as_view().len()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.as_view().is_empty()
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.as_view().contains(value)
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  pub fn is_subset(&self, other: &BitSetRef<'_, T>) -> bool {
+    self.as_view().is_subset(other)
+  }
+
+  /// Copies the set into an owned `BitSet`.
+  pub fn to_bitset(&self) -> BitSet<T> {
+    self.as_view().to_bitset()
+  }
+
+  /// Adds a value to the set, growing the file if necessary.
+  ///
+  /// Returns whether the value was not present in the set.
+  pub fn insert(&mut self, value: usize) -> io::Result<bool> {
+    let index = value / T::NUM_BITS;
+    if index >= self.num_blocks() {
+      self.grow(index + 1)?;
+    }
+
+    let blk = &mut self.blocks_mut()[index];
+    let mask = T::one().shl_bits(value % T::NUM_BITS);
+    let present = blk.bit_and(mask) != T::zero();
+    *blk = blk.bit_or(mask);
+    Ok(!present)
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&mut self, value: usize) -> bool {
+    match self.blocks_mut().get_mut(value / T::NUM_BITS) {
+      Some(blk) => {
+        let mask = T::one().shl_bits(value % T::NUM_BITS);
+        let present = blk.bit_and(mask) != T::zero();
+        *blk = blk.bit_and(mask.bit_not());
+        present
+      }
+      None => false,
+    }
+  }
+
+  /// Clear the set, removing all elements.
+  ///
+  /// Note that this method has no effect on the size of the file.
+  pub fn clear(&mut self) {
+    self.blocks_mut().fill(T::zero());
+  }
+
+  /// Writes all modifications of the set to the file, and waits for the writes
+  /// to complete.
+  pub fn flush(&self) -> io::Result<()> {
+    // SAFETY: `ptr` and `len` describe the current mapping.
+    let ret = unsafe {
+      libc::msync(self.ptr as *mut libc::c_void, self.len, libc::MS_SYNC)
+    };
+    if ret != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+}
+
+impl<T> Drop for MmapBitSet<T> {
+  fn drop(&mut self) {
+    // SAFETY: `ptr` and `len` describe the current mapping, which is not used
+    // after this.
+    unsafe {
+      libc::munmap(self.ptr as *mut libc::c_void, self.len);
+    }
+  }
+}
+
+impl<T> Debug for MmapBitSet<T>
+where
+  T: PlainBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.as_view().fmt(f)
+  }
+}
+
+/// A read-only bit set backed by a memory-mapped file.
+///
+/// This struct is created by the [`open_read_only`] method on
+/// [`MmapBitSet`]s, and provides the queries of `MmapBitSet`.
+///
+/// [`MmapBitSet`]: struct.MmapBitSet.html
+/// [`open_read_only`]: struct.MmapBitSet.html#method.open_read_only
+pub struct ReadOnlyMmapBitSet<T = usize> {
+  ptr: *mut u8,
+  /// Length of the mapping in bytes, including the header.
+  len: usize,
+  _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for ReadOnlyMmapBitSet<T> {}
+unsafe impl<T: Sync> Sync for ReadOnlyMmapBitSet<T> {}
+
+impl<T> ReadOnlyMmapBitSet<T>
+where
+  T: PlainBlock,
+{
+  /// Returns a read-only view of the set.
+  pub fn as_view(&self) -> BitSetRef<'_, T> {
+    // SAFETY: `ptr` and `len` describe the current mapping.
+    BitSetRef::new(unsafe { blocks(self.ptr, self.len) })
+  }
+
+  /// Iterates over the set, producing `usize`s representing the elements in
+  /// the set, in ascending order.
+  pub fn iter(&self) -> Iter<'_, T> {
+    self.as_view().iter()
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.as_view().len()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.as_view().is_empty()
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.as_view().contains(value)
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  pub fn is_subset(&self, other: &BitSetRef<'_, T>) -> bool {
+    self.as_view().is_subset(other)
+  }
+
+  /// Copies the set into an owned `BitSet`.
+  pub fn to_bitset(&self) -> BitSet<T> {
+    self.as_view().to_bitset()
+  }
+}
+
+impl<T> Drop for ReadOnlyMmapBitSet<T> {
+  fn drop(&mut self) {
+    // SAFETY: `ptr` and `len` describe the current mapping, which is not used
+    // after this.
+    unsafe {
+      libc::munmap(self.ptr as *mut libc::c_void, self.len);
+    }
+  }
+}
+
+impl<T> Debug for ReadOnlyMmapBitSet<T>
+where
+  T: PlainBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.as_view().fmt(f)
+  }
+}
+
+/// Opens the file at `path`, for writing too if `write` is true, and validates
+/// its header for blocks of type `T`. Returns the file and its length.
+fn open_file<T: PlainBlock>(path: &Path, write: bool) -> io::Result<(File, usize)> {
+  let mut file = OpenOptions::new().read(true).write(write).open(path)?;
+  let file_len = file.metadata()?.len();
+  let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+  if file_len < HEADER_LEN as u64 {
+    return Err(invalid("bittyset file is shorter than its header"));
+  }
+  let header = Header::read::<T, _>(&mut file)?;
+  if header.byte_len() != Some(file_len) {
+    return Err(invalid("bittyset file length does not match its header"));
+  }
+  let len = usize::try_from(file_len).map_err(|_| invalid("bittyset file is too large"))?;
+  Ok((file, len))
+}
+
+/// Maps the first `len` bytes of `file` with the protection `prot`.
+fn map(file: &File, len: usize, prot: libc::c_int) -> io::Result<*mut u8> {
+  // SAFETY: we map a fresh region, so no existing memory is affected.
+  let ptr = unsafe {
+    libc::mmap(ptr::null_mut(), len, prot, libc::MAP_SHARED, file.as_raw_fd(), 0)
+  };
+  if ptr == libc::MAP_FAILED {
+    return Err(io::Error::last_os_error());
+  }
+  Ok(ptr as *mut u8)
+}
+
+/// Returns the blocks of the mapping at `ptr` of `len` bytes.
+///
+/// # Safety
+///
+/// `ptr` and `len` must describe a live mapping of a validated file, which
+/// outlives `'a`.
+unsafe fn blocks<'a, T: PlainBlock>(ptr: *const u8, len: usize) -> &'a [T] {
+  // SAFETY: the blocks follow the header, which keeps them aligned for any
+  // primitive integer type, and the header is validated on open.
+  let num_blocks = (len - HEADER_LEN) / mem::size_of::<T>();
+  unsafe { slice::from_raw_parts(ptr.add(HEADER_LEN) as *const T, num_blocks) }
+}
@@ -0,0 +1,391 @@
+use std::borrow::Cow;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops::{
+  BitOr, BitOrAssign, BitAnd, BitAndAssign, Sub, SubAssign, BitXor, BitXorAssign
+};
+use crate::{BitBlock, BitSet, Iter};
+
+/// A trait for the elements of an [`OffsetBitSet`], mapping them to `u64`
+/// keys in an order-preserving way.
+///
+/// [`OffsetBitSet`]: struct.OffsetBitSet.html
+pub trait OffsetKey: Copy {
+  /// Converts the value to its key.
+  fn to_key(self) -> u64;
+
+  /// Converts a key back to the value.
+  fn from_key(key: u64) -> Self;
+}
+
+impl OffsetKey for u64 {
+  #[inline]
+  fn to_key(self) -> u64 {
+    self
+  }
+
+  #[inline]
+  fn from_key(key: u64) -> Self {
+    key
+  }
+}
+
+impl OffsetKey for i64 {
+  #[inline]
+  fn to_key(self) -> u64 {
+    (self as u64) ^ (1 << 63)
+  }
+
+  #[inline]
+  fn from_key(key: u64) -> Self {
+    (key ^ (1 << 63)) as i64
+  }
+}
+
+/// A set of `i64`s or `u64`s stored in a `BitSet` starting at a base offset,
+/// so that sets of large or negative values only take space for the span of
+/// their elements.
+///
+/// The base is a multiple of the number of bits of `T`, and is lowered
+/// automatically when smaller values are inserted. The binary operators align
+/// the bases of the operands.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::OffsetBitSet;
+///
+/// let mut set1 = <OffsetBitSet<i64>>::new();
+/// set1.insert(1_700_000_000_000);
+/// set1.insert(1_700_000_000_042);
+/// set1.insert(1_699_999_999_000);
+///
+/// let set2 = [1_699_999_999_990, 1_700_000_000_042].iter().cloned().collect::<OffsetBitSet<i64>>();
+///
+/// assert!(set1.contains(1_700_000_000_042));
+/// assert_eq!((&set1 & &set2).iter().collect::<Vec<_>>(), vec![1_700_000_000_042]);
+/// assert_eq!((&set1 | &set2).len(), 4);
+/// assert!(set1.as_bitset().as_blocks().len() < 100);
+///
+/// let set3 = [-3, -1, 2].iter().cloned().collect::<OffsetBitSet<i64>>();
+/// assert_eq!(set3.iter().collect::<Vec<_>>(), vec![-3, -1, 2]);
+/// ```
+pub struct OffsetBitSet<K, T = usize> {
+  set: BitSet<T>,
+  /// Index of the block holding the lowest bit of `set`, counted in blocks of
+  /// the key space.
+  base: u64,
+  marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K, T> OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  /// Creates a new empty `OffsetBitSet`.
+  pub fn new() -> Self {
+    Self {
+      set: BitSet::new(),
+      base: 0,
+      marker: PhantomData,
+    }
+  }
+
+  /// Returns the smallest value the underlying bit vector can hold without
+  /// lowering the base.
+  pub fn base(&self) -> K {
+    K::from_key(self.base * T::NUM_BITS as u64)
+  }
+
+  /// Returns the underlying set of the elements relative to the base.
+  pub fn as_bitset(&self) -> &BitSet<T> {
+    &self.set
+  }
+
+  /// Iterates over the set, producing the elements in ascending order.
+  pub fn iter(&self) -> OffsetIter<'_, K, T> {
+    OffsetIter {
+      iter: self.set.iter(),
+      base: self.base * T::NUM_BITS as u64,
+      marker: PhantomData,
+    }
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.set.len()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.set.as_blocks().is_empty()
+  }
+
+  /// Clear the set, removing all elements.
+  pub fn clear(&mut self) {
+    self.set.clear();
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: K) -> bool {
+    self.locate(value.to_key()).is_some_and(|i| self.set.contains(i))
+  }
+
+  /// Adds a value to the set. Returns whether the value was absent from the
+  /// set.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the span of the elements exceeds `usize::MAX` bits.
+  pub fn insert(&mut self, value: K) -> bool {
+    let key = value.to_key();
+    let kblk = key / T::NUM_BITS as u64;
+
+    if self.is_empty() {
+      self.set.clear();
+      self.base = kblk;
+    } else if kblk < self.base {
+      // Leave as many blocks below as there are already, so that inserting in
+      // descending order takes amortized constant time.
+      let headroom = self.set.as_blocks().len() as u64;
+      self.rebase(kblk.saturating_sub(headroom));
+    }
+
+    let index = self.locate(key).expect("capacity overflow");
+    self.set.insert(index)
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&mut self, value: K) -> bool {
+    match self.locate(value.to_key()) {
+      Some(i) => self.set.remove(i),
+      None => false,
+    }
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  pub fn is_subset(&self, other: &Self) -> bool {
+    let blocks = other.set.as_blocks();
+    self.set.as_blocks().iter().enumerate().all(|(i, &blk)| {
+      let other_blk = (self.base + i as u64).checked_sub(other.base)
+        .and_then(|j| usize::try_from(j).ok())
+        .and_then(|j| blocks.get(j).copied())
+        .unwrap_or(T::zero());
+      blk.bit_and(other_blk.bit_not()) == T::zero()
+    })
+  }
+
+  /// Returns the index of the bit of `key` in `set`, if it is not below the
+  /// base and fits in `usize`.
+  fn locate(&self, key: u64) -> Option<usize> {
+    let nblks = (key / T::NUM_BITS as u64).checked_sub(self.base)?;
+    usize::try_from(nblks).ok()?
+      .checked_mul(T::NUM_BITS)?
+      .checked_add((key % T::NUM_BITS as u64) as usize)
+  }
+
+  /// Lowers the base to `base`.
+  fn rebase(&mut self, base: u64) {
+    debug_assert!(base <= self.base);
+    let nblks = usize::try_from(self.base - base).expect("capacity overflow");
+    self.set.prepend_zero_blocks(nblks);
+    self.base = base;
+  }
+
+  /// Aligns the base of `self` with `rhs`, returning the set of `rhs` relative
+  /// to the base of `self`.
+  fn align<'b>(&mut self, rhs: &'b Self) -> Cow<'b, BitSet<T>> {
+    if rhs.is_empty() {
+      return Cow::Borrowed(&rhs.set);
+    }
+    if self.is_empty() {
+      self.set.clear();
+      self.base = rhs.base;
+    } else if rhs.base < self.base {
+      self.rebase(rhs.base);
+    }
+
+    if rhs.base == self.base {
+      Cow::Borrowed(&rhs.set)
+    } else {
+      let mut rhs = rhs.clone();
+      rhs.rebase(self.base);
+      Cow::Owned(rhs.set)
+    }
+  }
+
+  /// Returns the index of the first non-zero block in the key space and the
+  /// blocks from it, which identify the set regardless of its base.
+  fn normalized(&self) -> (u64, &[T]) {
+    let blocks = self.set.as_blocks();
+    match blocks.iter().position(|&blk| blk != T::zero()) {
+      Some(i) => (self.base + i as u64, &blocks[i..]),
+      None => (0, &[]),
+    }
+  }
+}
+
+impl<K, T> Clone for OffsetBitSet<K, T>
+where
+  T: BitBlock,
+{
+  fn clone(&self) -> Self {
+    Self {
+      set: self.set.clone(),
+      base: self.base,
+      marker: PhantomData,
+    }
+  }
+}
+
+impl<K, T> Default for OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<K, T> PartialEq for OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  fn eq(&self, other: &Self) -> bool {
+    self.normalized() == other.normalized()
+  }
+}
+
+impl<K, T> Eq for OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{}
+
+impl<K, T> Hash for OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.normalized().hash(state);
+  }
+}
+
+impl<K, T> Debug for OffsetBitSet<K, T>
+where
+  K: OffsetKey + Debug,
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_set().entries(self).finish()
+  }
+}
+
+impl<'a, K, T> IntoIterator for &'a OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  type IntoIter = OffsetIter<'a, K, T>;
+  type Item = K;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<K, T> FromIterator<K> for OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+    let mut set = Self::new();
+    set.extend(iter);
+    set
+  }
+}
+
+impl<K, T> Extend<K> for OffsetBitSet<K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+    for x in iter {
+      self.insert(x);
+    }
+  }
+}
+
+macro_rules! offset_op_impl {
+  ($name:ident, $method:ident, $assign_name:ident, $assign_method:ident) => {
+    impl<K: OffsetKey, T: BitBlock> $name<OffsetBitSet<K, T>> for OffsetBitSet<K, T> {
+      type Output = OffsetBitSet<K, T>;
+
+      fn $method(mut self, rhs: OffsetBitSet<K, T>) -> OffsetBitSet<K, T> {
+        self.$assign_method(&rhs);
+        self
+      }
+    }
+
+    impl<'a, K: OffsetKey, T: BitBlock> $name<&'a OffsetBitSet<K, T>> for &'a OffsetBitSet<K, T> {
+      type Output = OffsetBitSet<K, T>;
+
+      fn $method(self, rhs: &'a OffsetBitSet<K, T>) -> OffsetBitSet<K, T> {
+        let mut lhs = self.clone();
+        lhs.$assign_method(rhs);
+        lhs
+      }
+    }
+
+    impl<K: OffsetKey, T: BitBlock> $assign_name<OffsetBitSet<K, T>> for OffsetBitSet<K, T> {
+      fn $assign_method(&mut self, rhs: OffsetBitSet<K, T>) {
+        self.$assign_method(&rhs);
+      }
+    }
+
+    impl<'a, K: OffsetKey, T: BitBlock> $assign_name<&'a OffsetBitSet<K, T>> for OffsetBitSet<K, T> {
+      fn $assign_method(&mut self, rhs: &'a OffsetBitSet<K, T>) {
+        let rhs = self.align(rhs);
+        self.set.$assign_method(&*rhs);
+      }
+    }
+  };
+}
+
+offset_op_impl!(BitOr, bitor, BitOrAssign, bitor_assign);
+offset_op_impl!(BitAnd, bitand, BitAndAssign, bitand_assign);
+offset_op_impl!(Sub, sub, SubAssign, sub_assign);
+offset_op_impl!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+/// An iterator for `OffsetBitSet`.
+///
+/// This struct is created by the [`iter`] method on [`OffsetBitSet`]s.
+///
+/// [`OffsetBitSet`]: struct.OffsetBitSet.html
+/// [`iter`]: struct.OffsetBitSet.html#method.iter
+pub struct OffsetIter<'a, K, T> {
+  iter: Iter<'a, T>,
+  base: u64,
+  marker: PhantomData<fn() -> K>,
+}
+
+impl<'a, K, T> Iterator for OffsetIter<'a, K, T>
+where
+  K: OffsetKey,
+  T: BitBlock,
+{
+  type Item = K;
+
+  fn next(&mut self) -> Option<K> {
+    self.iter.next().map(|i| K::from_key(self.base + i as u64))
+  }
+}
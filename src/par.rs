@@ -0,0 +1,217 @@
+use rayon::iter::{
+  FromParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+  ParallelExtend, ParallelIterator, IndexedParallelIterator,
+};
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use crate::{BitBlock, BitSet, Iter};
+
+/// Minimum number of blocks processed by a single task.
+const MIN_TASK_BLOCKS: usize = 1024;
+
+/// A parallel iterator for `BitSet`.
+///
+/// This struct is created by the [`par_iter`] method on [`BitSet`]s.
+///
+/// [`BitSet`]: struct.BitSet.html
+/// [`par_iter`]: struct.BitSet.html#method.par_iter
+pub struct ParIter<'a, T> {
+  slice: &'a [T],
+}
+
+impl<'a, T> ParallelIterator for ParIter<'a, T>
+where
+  T: BitBlock + Sync,
+{
+  type Item = usize;
+
+  fn drive_unindexed<C>(self, consumer: C) -> C::Result
+  where
+    C: UnindexedConsumer<Self::Item>,
+  {
+    bridge_unindexed(BlocksProducer { slice: self.slice, base: 0 }, consumer)
+  }
+}
+
+struct BlocksProducer<'a, T> {
+  slice: &'a [T],
+  /// Index of the first block of `slice` in the whole bit vector.
+  base: usize,
+}
+
+impl<'a, T> UnindexedProducer for BlocksProducer<'a, T>
+where
+  T: BitBlock + Sync,
+{
+  type Item = usize;
+
+  /// Splits the blocks at the block where half of the elements are reached,
+  /// so that both halves have about the same number of elements.
+  fn split(self) -> (Self, Option<Self>) {
+    if self.slice.len() < MIN_TASK_BLOCKS * 2 {
+      return (self, None);
+    }
+
+    let total = count_ones(self.slice);
+    let mut acc = 0;
+    let mid = self.slice.iter()
+      .position(|x| {
+        acc += x.count_ones() as usize;
+        acc * 2 >= total
+      })
+      .unwrap_or(0)
+      .clamp(MIN_TASK_BLOCKS, self.slice.len() - MIN_TASK_BLOCKS);
+
+    let (left, right) = self.slice.split_at(mid);
+    (
+      Self { slice: left, base: self.base },
+      Some(Self { slice: right, base: self.base + mid }),
+    )
+  }
+
+  fn fold_with<F>(self, folder: F) -> F
+  where
+    F: Folder<Self::Item>,
+  {
+    let offset = self.base * T::NUM_BITS;
+    folder.consume_iter(
+      Iter::from_slice(self.slice, self.slice.len() * T::NUM_BITS).map(|x| x + offset))
+  }
+}
+
+fn count_ones<T: BitBlock>(slice: &[T]) -> usize {
+  slice.iter().map(|x| x.count_ones() as usize).sum()
+}
+
+impl<T> BitSet<T>
+where
+  T: BitBlock + Send + Sync,
+{
+  /// Iterates over the `BitSet` in parallel, producing `usize`s representing
+  /// the elements in the set.
+  ///
+  /// The set is split at block boundaries so that the parts have about the
+  /// same number of elements.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::BitSet;
+  /// use rayon::prelude::*;
+  ///
+  /// let set1 = (0..100000).step_by(3).collect::<BitSet>();
+  ///
+  /// assert_eq!(set1.par_iter().sum::<usize>(), set1.iter().sum());
+  /// ```
+  pub fn par_iter(&self) -> ParIter<'_, T> {
+    ParIter { slice: self.as_blocks() }
+  }
+
+  /// Returns the number of elements in the set, counting in parallel.
+  pub fn par_len(&self) -> usize {
+    self.as_blocks()
+      .par_iter()
+      .with_min_len(MIN_TASK_BLOCKS)
+      .map(|x| x.count_ones() as usize)
+      .sum()
+  }
+
+  /// Returns whether the set is a subset of `other`, comparing in parallel.
+  pub fn par_is_subset(&self, other: &Self) -> bool {
+    let (blocks1, blocks2) = (self.as_blocks(), other.as_blocks());
+    if blocks1.len() > blocks2.len() {
+      return false;
+    }
+
+    blocks1.par_iter()
+      .zip(blocks2)
+      .with_min_len(MIN_TASK_BLOCKS)
+      .all(|(&x, &y)| x.bit_and(y.bit_not()) == T::zero())
+  }
+
+  /// Computes the union of the set and `other` in parallel.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::BitSet;
+  ///
+  /// let set1 = (0..100000).step_by(3).collect::<BitSet>();
+  /// let set2 = (0..100000).step_by(5).collect::<BitSet>();
+  ///
+  /// assert_eq!(set1.par_union(&set2), &set1 | &set2);
+  /// ```
+  pub fn par_union(&self, other: &Self) -> Self {
+    let nblks = self.as_blocks().len().max(other.as_blocks().len());
+    par_combine(self, other, nblks, |x, y| x.bit_or(y))
+  }
+
+  /// Computes the intersection of the set and `other` in parallel.
+  pub fn par_intersection(&self, other: &Self) -> Self {
+    let nblks = self.as_blocks().len().min(other.as_blocks().len());
+    par_combine(self, other, nblks, |x, y| x.bit_and(y))
+  }
+
+  /// Computes the difference of the set and `other` in parallel.
+  pub fn par_difference(&self, other: &Self) -> Self {
+    par_combine(self, other, self.as_blocks().len(), |x, y| x.bit_and(y.bit_not()))
+  }
+}
+
+/// Combines the first `nblks` blocks of two sets with `f` in parallel, with
+/// missing blocks treated as zeros.
+fn par_combine<T, F>(lhs: &BitSet<T>, rhs: &BitSet<T>, nblks: usize, f: F) -> BitSet<T>
+where
+  T: BitBlock + Send + Sync,
+  F: Fn(T, T) -> T + Sync,
+{
+  let (blocks1, blocks2) = (lhs.as_blocks(), rhs.as_blocks());
+  let vec = (0..nblks)
+    .into_par_iter()
+    .with_min_len(MIN_TASK_BLOCKS)
+    .map(|i| f(
+      blocks1.get(i).copied().unwrap_or_else(T::zero),
+      blocks2.get(i).copied().unwrap_or_else(T::zero)))
+    .collect();
+  BitSet::from_vec(vec)
+}
+
+impl<'a, T> IntoParallelIterator for &'a BitSet<T>
+where
+  T: BitBlock + Send + Sync,
+{
+  type Iter = ParIter<'a, T>;
+  type Item = usize;
+
+  fn into_par_iter(self) -> Self::Iter {
+    self.par_iter()
+  }
+}
+
+impl<T> FromParallelIterator<usize> for BitSet<T>
+where
+  T: BitBlock + Send + Sync,
+{
+  fn from_par_iter<I>(par_iter: I) -> Self
+  where
+    I: IntoParallelIterator<Item = usize>,
+  {
+    par_iter.into_par_iter()
+      .fold(BitSet::new, |mut set, x| {
+        set.insert(x);
+        set
+      })
+      .reduce(BitSet::new, |set1, set2| set1 | set2)
+  }
+}
+
+impl<T> ParallelExtend<usize> for BitSet<T>
+where
+  T: BitBlock + Send + Sync,
+{
+  fn par_extend<I>(&mut self, par_iter: I)
+  where
+    I: IntoParallelIterator<Item = usize>,
+  {
+    *self |= BitSet::from_par_iter(par_iter);
+  }
+}
@@ -0,0 +1,184 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Range;
+use crate::{BitBlock, BitSet};
+
+/// The policy of [`RangeAllocator::alloc`] for choosing among the runs of
+/// free bits long enough.
+///
+/// [`RangeAllocator::alloc`]: struct.RangeAllocator.html#method.alloc
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fit {
+  /// The lowest run.
+  First,
+  /// The first run after the end of the last range allocated by `Fit::Next`,
+  /// wrapping around.
+  Next,
+  /// The shortest run, and the lowest among the shortest.
+  Best,
+}
+
+/// Statistics of the free bits of a [`RangeAllocator`].
+///
+/// [`RangeAllocator`]: struct.RangeAllocator.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FreeSpaceStats {
+  /// The number of free bits.
+  pub free: usize,
+  /// The number of maximal runs of free bits.
+  pub free_runs: usize,
+  /// The length of the longest run of free bits.
+  pub largest_free_run: usize,
+}
+
+impl FreeSpaceStats {
+  /// Returns the fraction of the free bits outside of the longest run, from
+  /// `0.0` if the free bits are contiguous to nearly `1.0`.
+  pub fn fragmentation(&self) -> f64 {
+    if self.free == 0 {
+      0.0
+    } else {
+      1.0 - self.largest_free_run as f64 / self.free as f64
+    }
+  }
+}
+
+/// An allocator of ranges of consecutive bits from `0..capacity`, as for the
+/// free-space bitmaps of file systems.
+///
+/// The allocated bits are the ones of a `BitSet`. The free runs are found by
+/// scanning the blocks for zeros, with the policies of [`Fit`].
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::{Fit, RangeAllocator};
+///
+/// let mut pages = <RangeAllocator>::new(100);
+/// assert_eq!(pages.alloc(10, Fit::First), Some(0..10));
+/// assert_eq!(pages.alloc(5, Fit::First), Some(10..15));
+/// assert_eq!(pages.alloc(20, Fit::First), Some(15..35));
+/// assert!(pages.free(10..15));
+///
+/// assert_eq!(pages.alloc(4, Fit::Best), Some(10..14));
+/// assert_eq!(pages.alloc(4, Fit::First), Some(35..39));
+/// assert_eq!(pages.alloc(100, Fit::First), None);
+///
+/// let stats = pages.stats();
+/// assert_eq!((stats.free, stats.free_runs, stats.largest_free_run), (62, 2, 61));
+/// ```
+///
+/// [`Fit`]: enum.Fit.html
+#[derive(Clone)]
+pub struct RangeAllocator<T = usize> {
+  set: BitSet<T>,
+  capacity: usize,
+  cursor: usize,
+}
+
+impl<T> RangeAllocator<T>
+where
+  T: BitBlock,
+{
+  /// Creates an allocator of the free bits `0..capacity`.
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      set: BitSet::new(),
+      capacity,
+      cursor: 0,
+    }
+  }
+
+  /// Returns the number of bits managed by the allocator.
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Returns the set of the allocated bits.
+  pub fn as_bitset(&self) -> &BitSet<T> {
+    &self.set
+  }
+
+  /// Returns whether the bit `index` is allocated.
+  pub fn is_allocated(&self, index: usize) -> bool {
+    self.set.contains(index)
+  }
+
+  /// Allocates a range of `len` free bits chosen by `fit`, and returns it.
+  /// Returns `None` if there is no run of `len` free bits.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `len` is zero.
+  pub fn alloc(&mut self, len: usize, fit: Fit) -> Option<Range<usize>> {
+    assert!(len > 0, "cannot allocate an empty range");
+
+    let fits = |run: &Range<usize>| run.len() >= len;
+    let start = match fit {
+      Fit::First => self.free_runs(0..self.capacity).find(fits)?.start,
+      Fit::Next => {
+        self.free_runs(self.cursor..self.capacity).find(fits)
+          .or_else(|| self.free_runs(0..self.capacity).find(fits))?
+          .start
+      },
+      Fit::Best => self.free_runs(0..self.capacity).filter(fits).min_by_key(Range::len)?.start,
+    };
+
+    let range = start..start + len;
+    self.set.extend_ranges(Some(range.clone()));
+    if fit == Fit::Next {
+      self.cursor = range.end;
+    }
+    Some(range)
+  }
+
+  /// Allocates all the bits in `range` if they are all free. Returns whether
+  /// they were allocated.
+  pub fn alloc_range(&mut self, range: Range<usize>) -> bool {
+    if range.is_empty() {
+      return true;
+    }
+    if range.end > self.capacity || self.free_runs(range.clone()).next() != Some(range.clone()) {
+      return false;
+    }
+    self.set.extend_ranges(Some(range));
+    true
+  }
+
+  /// Frees all the bits in `range` if they are all allocated. Returns whether
+  /// they were freed.
+  pub fn free(&mut self, range: Range<usize>) -> bool {
+    if range.end > self.capacity || self.free_runs(range.clone()).next().is_some() {
+      return false;
+    }
+    self.set.remove_ranges(Some(range));
+    true
+  }
+
+  /// Returns the statistics of the free bits.
+  pub fn stats(&self) -> FreeSpaceStats {
+    self.free_runs(0..self.capacity).fold(
+      FreeSpaceStats { free: 0, free_runs: 0, largest_free_run: 0 },
+      |stats, run| FreeSpaceStats {
+        free: stats.free + run.len(),
+        free_runs: stats.free_runs + 1,
+        largest_free_run: stats.largest_free_run.max(run.len()),
+      },
+    )
+  }
+
+  fn free_runs(&self, within: Range<usize>) -> crate::Runs<'_, T> {
+    self.set.runs_of_zeros(within)
+  }
+}
+
+impl<T> Debug for RangeAllocator<T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_struct("RangeAllocator")
+      .field("capacity", &self.capacity)
+      .field("allocated", &self.set.runs().collect::<Vec<_>>())
+      .finish()
+  }
+}
@@ -0,0 +1,561 @@
+//! Block kernels for the set operations, with runtime CPU feature dispatch.
+//!
+//! Blocks of plain integer types are processed as bytes, since the bitwise
+//! operations and population counts do not depend on the block layout. On
+//! `x86_64`, AVX-512, AVX2 and SSE4.2 kernels are selected at runtime, with
+//! a portable fallback working on `u64` words. Blocks of other types are
+//! processed one at a time.
+
+use crate::BitBlock;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+  /// The left operand.
+  First,
+  Or,
+  And,
+  /// The left operand and the complement of the right operand.
+  AndNot,
+  Xor,
+}
+
+/// A binary operation on blocks, selected at compile time.
+pub(crate) trait BinOp {
+  const OP: Op;
+}
+
+macro_rules! bin_op {
+  ($name:ident) => {
+    pub(crate) struct $name;
+
+    impl BinOp for $name {
+      const OP: Op = Op::$name;
+    }
+  }
+}
+
+bin_op!(First);
+bin_op!(Or);
+bin_op!(And);
+bin_op!(AndNot);
+bin_op!(Xor);
+
+#[inline(always)]
+fn block_op<T: BitBlock, O: BinOp>(a: T, b: T) -> T {
+  match O::OP {
+    Op::First => a,
+    Op::Or => a.bit_or(b),
+    Op::And => a.bit_and(b),
+    Op::AndNot => a.bit_and(b.bit_not()),
+    Op::Xor => a.bit_xor(b),
+  }
+}
+
+/// Computes `dst[i] = op(dst[i], src[i])` for the common prefix of `dst` and
+/// `src`.
+pub(crate) fn apply_assign<T: BitBlock, O: BinOp>(dst: &mut [T], src: &[T]) {
+  let n = dst.len().min(src.len());
+  let (dst, src) = (&mut dst[..n], &src[..n]);
+
+  if let Some(src) = T::as_bytes(src) {
+    let dst = T::as_bytes_mut(dst).expect("inconsistent BitBlock::as_bytes");
+    bytes::apply_assign::<O>(dst, src);
+  } else {
+    for (x, &y) in dst.iter_mut().zip(src) {
+      *x = block_op::<T, O>(*x, y);
+    }
+  }
+}
+
+/// Counts the ones of `op(a[i], b[i])` for the common prefix of `a` and `b`.
+pub(crate) fn count_ones_op<T: BitBlock, O: BinOp>(a: &[T], b: &[T]) -> usize {
+  let n = a.len().min(b.len());
+  let (a, b) = (&a[..n], &b[..n]);
+
+  match (T::as_bytes(a), T::as_bytes(b)) {
+    (Some(a), Some(b)) => bytes::count_ones::<O>(a, b),
+    _ => a.iter().zip(b).map(|(&x, &y)| block_op::<T, O>(x, y).count_ones() as usize).sum(),
+  }
+}
+
+/// Counts the ones of `a`.
+pub(crate) fn count_ones<T: BitBlock>(a: &[T]) -> usize {
+  count_ones_op::<T, First>(a, a)
+}
+
+/// Returns whether `op(a[i], b[i])` is non-zero for any `i` of the common
+/// prefix of `a` and `b`.
+pub(crate) fn any_op<T: BitBlock, O: BinOp>(a: &[T], b: &[T]) -> bool {
+  let n = a.len().min(b.len());
+  let (a, b) = (&a[..n], &b[..n]);
+
+  match (T::as_bytes(a), T::as_bytes(b)) {
+    (Some(a), Some(b)) => bytes::any::<O>(a, b),
+    _ => a.iter().zip(b).any(|(&x, &y)| block_op::<T, O>(x, y) != T::zero()),
+  }
+}
+
+/// Returns whether `a` and `b` are equal.
+pub(crate) fn eq<T: BitBlock>(a: &[T], b: &[T]) -> bool {
+  a.len() == b.len() && !any_op::<T, Xor>(a, b)
+}
+
+/// Returns whether `a & !b` is zero, where `b` is treated as padded with
+/// zeros.
+pub(crate) fn is_subset<T: BitBlock>(a: &[T], b: &[T]) -> bool {
+  !any_op::<T, AndNot>(a, b) && a.iter().skip(b.len()).all(|&blk| blk == T::zero())
+}
+
+/// Dispatch of the byte kernels. The slices must have equal lengths.
+mod bytes {
+  use super::{scalar, BinOp};
+  #[cfg(target_arch = "x86_64")]
+  use super::x86;
+
+  pub(super) fn apply_assign<O: BinOp>(dst: &mut [u8], src: &[u8]) {
+    debug_assert_eq!(dst.len(), src.len());
+    #[cfg(target_arch = "x86_64")]
+    {
+      let f = x86::features();
+      // SAFETY: the required CPU features are detected.
+      unsafe {
+        if f.avx512 {
+          return x86::apply_assign_avx512::<O>(dst, src);
+        } else if f.avx2 {
+          return x86::apply_assign_avx2::<O>(dst, src);
+        } else if f.sse42 {
+          return x86::apply_assign_sse42::<O>(dst, src);
+        }
+      }
+    }
+    scalar::apply_assign::<O>(dst, src)
+  }
+
+  pub(super) fn count_ones<O: BinOp>(a: &[u8], b: &[u8]) -> usize {
+    debug_assert_eq!(a.len(), b.len());
+    #[cfg(target_arch = "x86_64")]
+    {
+      let f = x86::features();
+      // SAFETY: the required CPU features are detected.
+      unsafe {
+        if f.avx512_popcnt {
+          return x86::count_ones_avx512::<O>(a, b);
+        } else if f.avx2 {
+          return x86::count_ones_avx2::<O>(a, b);
+        } else if f.sse42 {
+          return x86::count_ones_sse42::<O>(a, b);
+        }
+      }
+    }
+    scalar::count_ones::<O>(a, b)
+  }
+
+  pub(super) fn any<O: BinOp>(a: &[u8], b: &[u8]) -> bool {
+    debug_assert_eq!(a.len(), b.len());
+    #[cfg(target_arch = "x86_64")]
+    {
+      let f = x86::features();
+      // SAFETY: the required CPU features are detected.
+      unsafe {
+        if f.avx512 {
+          return x86::any_avx512::<O>(a, b);
+        } else if f.avx2 {
+          return x86::any_avx2::<O>(a, b);
+        } else if f.sse42 {
+          return x86::any_sse42::<O>(a, b);
+        }
+      }
+    }
+    scalar::any::<O>(a, b)
+  }
+}
+
+/// Portable byte kernels working on `u64` words. The slices must have equal
+/// lengths.
+mod scalar {
+  use super::BinOp;
+
+  #[inline(always)]
+  fn word_op<O: BinOp>(a: u64, b: u64) -> u64 {
+    super::block_op::<u64, O>(a, b)
+  }
+
+  #[inline(always)]
+  fn word(bytes: &[u8]) -> u64 {
+    u64::from_ne_bytes(bytes.try_into().unwrap())
+  }
+
+  #[inline]
+  pub(super) fn apply_assign<O: BinOp>(dst: &mut [u8], src: &[u8]) {
+    let mut dst = dst.chunks_exact_mut(8);
+    let mut src = src.chunks_exact(8);
+    for (x, y) in (&mut dst).zip(&mut src) {
+      x.copy_from_slice(&word_op::<O>(word(x), word(y)).to_ne_bytes());
+    }
+    for (x, &y) in dst.into_remainder().iter_mut().zip(src.remainder()) {
+      *x = word_op::<O>(*x as u64, y as u64) as u8;
+    }
+  }
+
+  #[inline]
+  pub(super) fn count_ones<O: BinOp>(a: &[u8], b: &[u8]) -> usize {
+    let a = a.chunks_exact(8);
+    let b = b.chunks_exact(8);
+    let tail = a.remainder().iter().zip(b.remainder())
+      .map(|(&x, &y)| (word_op::<O>(x as u64, y as u64) as u8).count_ones() as usize)
+      .sum::<usize>();
+    a.zip(b)
+      .map(|(x, y)| word_op::<O>(word(x), word(y)).count_ones() as usize)
+      .sum::<usize>() + tail
+  }
+
+  #[inline]
+  pub(super) fn any<O: BinOp>(a: &[u8], b: &[u8]) -> bool {
+    let a = a.chunks_exact(8);
+    let b = b.chunks_exact(8);
+    a.remainder().iter().zip(b.remainder())
+      .any(|(&x, &y)| word_op::<O>(x as u64, y as u64) as u8 != 0) ||
+      a.zip(b).any(|(x, y)| word_op::<O>(word(x), word(y)) != 0)
+  }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+  use std::arch::x86_64::*;
+  use super::{scalar, BinOp, Op};
+
+  #[derive(Clone, Copy)]
+  pub(super) struct Features {
+    pub(super) avx512: bool,
+    pub(super) avx512_popcnt: bool,
+    pub(super) avx2: bool,
+    pub(super) sse42: bool,
+  }
+
+  #[inline]
+  pub(super) fn features() -> Features {
+    let avx512 = is_x86_feature_detected!("avx512f");
+    Features {
+      avx512,
+      avx512_popcnt: avx512 && is_x86_feature_detected!("avx512vpopcntdq"),
+      avx2: is_x86_feature_detected!("avx2"),
+      sse42: is_x86_feature_detected!("sse4.2") && is_x86_feature_detected!("popcnt"),
+    }
+  }
+
+  #[inline]
+  #[target_feature(enable = "sse4.2")]
+  unsafe fn op128<O: BinOp>(a: __m128i, b: __m128i) -> __m128i {
+    match O::OP {
+      Op::First => a,
+      Op::Or => _mm_or_si128(a, b),
+      Op::And => _mm_and_si128(a, b),
+      Op::AndNot => _mm_andnot_si128(b, a),
+      Op::Xor => _mm_xor_si128(a, b),
+    }
+  }
+
+  #[inline]
+  #[target_feature(enable = "avx2")]
+  unsafe fn op256<O: BinOp>(a: __m256i, b: __m256i) -> __m256i {
+    match O::OP {
+      Op::First => a,
+      Op::Or => _mm256_or_si256(a, b),
+      Op::And => _mm256_and_si256(a, b),
+      Op::AndNot => _mm256_andnot_si256(b, a),
+      Op::Xor => _mm256_xor_si256(a, b),
+    }
+  }
+
+  #[inline]
+  #[target_feature(enable = "avx512f")]
+  unsafe fn op512<O: BinOp>(a: __m512i, b: __m512i) -> __m512i {
+    match O::OP {
+      Op::First => a,
+      Op::Or => _mm512_or_si512(a, b),
+      Op::And => _mm512_and_si512(a, b),
+      Op::AndNot => _mm512_andnot_si512(b, a),
+      Op::Xor => _mm512_xor_si512(a, b),
+    }
+  }
+
+  #[inline]
+  #[target_feature(enable = "sse4.2")]
+  unsafe fn load128<O: BinOp>(a: &[u8], b: &[u8], i: usize) -> __m128i {
+    op128::<O>(
+      _mm_loadu_si128(a.as_ptr().add(i * 16) as *const __m128i),
+      _mm_loadu_si128(b.as_ptr().add(i * 16) as *const __m128i))
+  }
+
+  #[inline]
+  #[target_feature(enable = "avx2")]
+  unsafe fn load256<O: BinOp>(a: &[u8], b: &[u8], i: usize) -> __m256i {
+    op256::<O>(
+      _mm256_loadu_si256(a.as_ptr().add(i * 32) as *const __m256i),
+      _mm256_loadu_si256(b.as_ptr().add(i * 32) as *const __m256i))
+  }
+
+  #[inline]
+  #[target_feature(enable = "avx512f")]
+  unsafe fn load512<O: BinOp>(a: &[u8], b: &[u8], i: usize) -> __m512i {
+    op512::<O>(
+      _mm512_loadu_si512(a.as_ptr().add(i * 64) as *const __m512i),
+      _mm512_loadu_si512(b.as_ptr().add(i * 64) as *const __m512i))
+  }
+
+  #[target_feature(enable = "sse4.2,popcnt")]
+  pub(super) unsafe fn apply_assign_sse42<O: BinOp>(dst: &mut [u8], src: &[u8]) {
+    let n = dst.len() / 16;
+    for i in 0..n {
+      let x = load128::<O>(dst, src, i);
+      _mm_storeu_si128(dst.as_mut_ptr().add(i * 16) as *mut __m128i, x);
+    }
+    scalar::apply_assign::<O>(&mut dst[n * 16..], &src[n * 16..]);
+  }
+
+  #[target_feature(enable = "avx2")]
+  pub(super) unsafe fn apply_assign_avx2<O: BinOp>(dst: &mut [u8], src: &[u8]) {
+    let n = dst.len() / 32;
+    for i in 0..n {
+      let x = load256::<O>(dst, src, i);
+      _mm256_storeu_si256(dst.as_mut_ptr().add(i * 32) as *mut __m256i, x);
+    }
+    scalar::apply_assign::<O>(&mut dst[n * 32..], &src[n * 32..]);
+  }
+
+  #[target_feature(enable = "avx512f")]
+  pub(super) unsafe fn apply_assign_avx512<O: BinOp>(dst: &mut [u8], src: &[u8]) {
+    let n = dst.len() / 64;
+    for i in 0..n {
+      let x = load512::<O>(dst, src, i);
+      _mm512_storeu_si512(dst.as_mut_ptr().add(i * 64) as *mut __m512i, x);
+    }
+    scalar::apply_assign::<O>(&mut dst[n * 64..], &src[n * 64..]);
+  }
+
+  /// Counts ones of 64-bit words with the `popcnt` instruction.
+  #[target_feature(enable = "sse4.2,popcnt")]
+  pub(super) unsafe fn count_ones_sse42<O: BinOp>(a: &[u8], b: &[u8]) -> usize {
+    scalar::count_ones::<O>(a, b)
+  }
+
+  /// Counts the ones of each 64-bit lane, with the nibble lookup method.
+  #[inline]
+  #[target_feature(enable = "avx2")]
+  unsafe fn popcount256(v: __m256i) -> __m256i {
+    let lookup = _mm256_setr_epi8(
+      0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+      0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4);
+    let low_mask = _mm256_set1_epi8(0x0f);
+    let lo = _mm256_and_si256(v, low_mask);
+    let hi = _mm256_and_si256(_mm256_srli_epi16::<4>(v), low_mask);
+    let counts = _mm256_add_epi8(
+      _mm256_shuffle_epi8(lookup, lo),
+      _mm256_shuffle_epi8(lookup, hi));
+    _mm256_sad_epu8(counts, _mm256_setzero_si256())
+  }
+
+  /// A carry-save adder, returning the carry and the sum bits of `a + b + c`.
+  #[inline]
+  #[target_feature(enable = "avx2")]
+  unsafe fn csa(a: __m256i, b: __m256i, c: __m256i) -> (__m256i, __m256i) {
+    let u = _mm256_xor_si256(a, b);
+    (_mm256_or_si256(_mm256_and_si256(a, b), _mm256_and_si256(u, c)), _mm256_xor_si256(u, c))
+  }
+
+  /// Counts ones with the Harley-Seal method, which reduces 16 vectors with
+  /// carry-save adders before counting.
+  #[target_feature(enable = "avx2")]
+  pub(super) unsafe fn count_ones_avx2<O: BinOp>(a: &[u8], b: &[u8]) -> usize {
+    let n = a.len() / 32;
+    let load = |i| load256::<O>(a, b, i);
+    let zero = _mm256_setzero_si256();
+    let mut total = zero;
+    let (mut ones, mut twos, mut fours, mut eights) = (zero, zero, zero, zero);
+    let (mut twos_a, mut twos_b, mut fours_a, mut fours_b, mut eights_a, mut eights_b);
+    let mut sixteens;
+
+    let mut i = 0;
+    while i + 16 <= n {
+      (twos_a, ones) = csa(ones, load(i), load(i + 1));
+      (twos_b, ones) = csa(ones, load(i + 2), load(i + 3));
+      (fours_a, twos) = csa(twos, twos_a, twos_b);
+      (twos_a, ones) = csa(ones, load(i + 4), load(i + 5));
+      (twos_b, ones) = csa(ones, load(i + 6), load(i + 7));
+      (fours_b, twos) = csa(twos, twos_a, twos_b);
+      (eights_a, fours) = csa(fours, fours_a, fours_b);
+      (twos_a, ones) = csa(ones, load(i + 8), load(i + 9));
+      (twos_b, ones) = csa(ones, load(i + 10), load(i + 11));
+      (fours_a, twos) = csa(twos, twos_a, twos_b);
+      (twos_a, ones) = csa(ones, load(i + 12), load(i + 13));
+      (twos_b, ones) = csa(ones, load(i + 14), load(i + 15));
+      (fours_b, twos) = csa(twos, twos_a, twos_b);
+      (eights_b, fours) = csa(fours, fours_a, fours_b);
+      (sixteens, eights) = csa(eights, eights_a, eights_b);
+      total = _mm256_add_epi64(total, popcount256(sixteens));
+      i += 16;
+    }
+
+    total = _mm256_slli_epi64::<4>(total);
+    total = _mm256_add_epi64(total, _mm256_slli_epi64::<3>(popcount256(eights)));
+    total = _mm256_add_epi64(total, _mm256_slli_epi64::<2>(popcount256(fours)));
+    total = _mm256_add_epi64(total, _mm256_slli_epi64::<1>(popcount256(twos)));
+    total = _mm256_add_epi64(total, popcount256(ones));
+    while i < n {
+      total = _mm256_add_epi64(total, popcount256(load(i)));
+      i += 1;
+    }
+
+    let mut lanes = [0u64; 4];
+    _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, total);
+    lanes.iter().sum::<u64>() as usize + scalar::count_ones::<O>(&a[n * 32..], &b[n * 32..])
+  }
+
+  #[target_feature(enable = "avx512f,avx512vpopcntdq")]
+  pub(super) unsafe fn count_ones_avx512<O: BinOp>(a: &[u8], b: &[u8]) -> usize {
+    let n = a.len() / 64;
+    let mut total = _mm512_setzero_si512();
+    for i in 0..n {
+      total = _mm512_add_epi64(total, _mm512_popcnt_epi64(load512::<O>(a, b, i)));
+    }
+    _mm512_reduce_add_epi64(total) as usize + scalar::count_ones::<O>(&a[n * 64..], &b[n * 64..])
+  }
+
+  #[target_feature(enable = "sse4.2,popcnt")]
+  pub(super) unsafe fn any_sse42<O: BinOp>(a: &[u8], b: &[u8]) -> bool {
+    let n = a.len() / 16;
+    for i in 0..n {
+      let x = load128::<O>(a, b, i);
+      if _mm_testz_si128(x, x) == 0 {
+        return true;
+      }
+    }
+    scalar::any::<O>(&a[n * 16..], &b[n * 16..])
+  }
+
+  #[target_feature(enable = "avx2")]
+  pub(super) unsafe fn any_avx2<O: BinOp>(a: &[u8], b: &[u8]) -> bool {
+    let n = a.len() / 32;
+    for i in 0..n {
+      let x = load256::<O>(a, b, i);
+      if _mm256_testz_si256(x, x) == 0 {
+        return true;
+      }
+    }
+    scalar::any::<O>(&a[n * 32..], &b[n * 32..])
+  }
+
+  #[target_feature(enable = "avx512f")]
+  pub(super) unsafe fn any_avx512<O: BinOp>(a: &[u8], b: &[u8]) -> bool {
+    let n = a.len() / 64;
+    for i in 0..n {
+      let x = load512::<O>(a, b, i);
+      if _mm512_test_epi64_mask(x, x) != 0 {
+        return true;
+      }
+    }
+    scalar::any::<O>(&a[n * 64..], &b[n * 64..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use quickcheck_macros::quickcheck;
+
+  /// Generates `len` pseudo-random bytes, with runs of zeros and ones.
+  fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut x = seed | 1;
+    (0..len)
+      .map(|_| {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        match x % 8 {
+          0 => 0,
+          1 => 0xff,
+          _ => (x >> 32) as u8,
+        }
+      })
+      .collect()
+  }
+
+  fn check_op<O: BinOp>(a: &[u8], b: &[u8]) -> bool {
+    let mut expected = a.to_vec();
+    scalar::apply_assign::<O>(&mut expected, b);
+    let expected_count = expected.iter().map(|x| x.count_ones() as usize).sum::<usize>();
+    let expected_any = expected_count != 0;
+
+    let mut dst = a.to_vec();
+    bytes::apply_assign::<O>(&mut dst, b);
+    let mut ok = dst == expected &&
+      scalar::count_ones::<O>(a, b) == expected_count &&
+      bytes::count_ones::<O>(a, b) == expected_count &&
+      scalar::any::<O>(a, b) == expected_any &&
+      bytes::any::<O>(a, b) == expected_any;
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+      let f = x86::features();
+      let check_assign = |apply: unsafe fn(&mut [u8], &[u8])| {
+        let mut dst = a.to_vec();
+        apply(&mut dst, b);
+        dst == expected
+      };
+
+      if f.sse42 {
+        ok &= check_assign(x86::apply_assign_sse42::<O>) &&
+          x86::count_ones_sse42::<O>(a, b) == expected_count &&
+          x86::any_sse42::<O>(a, b) == expected_any;
+      }
+      if f.avx2 {
+        ok &= check_assign(x86::apply_assign_avx2::<O>) &&
+          x86::count_ones_avx2::<O>(a, b) == expected_count &&
+          x86::any_avx2::<O>(a, b) == expected_any;
+      }
+      if f.avx512 {
+        ok &= check_assign(x86::apply_assign_avx512::<O>) &&
+          x86::any_avx512::<O>(a, b) == expected_any;
+      }
+      if f.avx512_popcnt {
+        ok &= x86::count_ones_avx512::<O>(a, b) == expected_count;
+      }
+    }
+
+    ok
+  }
+
+  #[quickcheck]
+  fn kernels_prop(seed1: u64, seed2: u64, len: u16) -> bool {
+    let len = len as usize % 3000;
+    let a = random_bytes(seed1, len);
+    let b = random_bytes(seed2, len);
+
+    check_op::<First>(&a, &b) &&
+      check_op::<Or>(&a, &b) &&
+      check_op::<And>(&a, &b) &&
+      check_op::<AndNot>(&a, &b) &&
+      check_op::<Xor>(&a, &b) &&
+      check_op::<Xor>(&a, &a)
+  }
+
+  #[test]
+  fn is_subset_tail() {
+    assert!(is_subset::<u16>(&[0], &[]));
+    assert!(is_subset::<u16>(&[1, 0, 0], &[3]));
+    assert!(!is_subset::<u16>(&[1, 0, 4], &[3]));
+    assert!(is_subset::<u16>(&[1], &[3, 4]));
+  }
+
+  #[quickcheck]
+  fn blocks_prop(vec1: Vec<u16>, vec2: Vec<u16>) -> bool {
+    let subset = vec1.iter().zip(&vec2).map(|(x, y)| x & y).collect::<Vec<_>>();
+
+    eq(&vec1, &vec1) &&
+      eq(&vec1, &vec2) == (vec1 == vec2) &&
+      is_subset(&subset, &vec1) &&
+      is_subset(&vec1, &vec2) ==
+        vec1.iter().enumerate().all(|(i, x)| x & !vec2.get(i).copied().unwrap_or(0) == 0) &&
+      count_ones(&vec1) == vec1.iter().map(|x| x.count_ones() as usize).sum::<usize>()
+  }
+}
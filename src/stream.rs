@@ -0,0 +1,400 @@
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::slice;
+use crate::{BitSet, PlainBlock};
+use crate::format::Header;
+
+/// A reader of the blocks of a serialized bit set, producing `io::Result<T>`s.
+///
+/// The serialized form is the one written by [`BitSet::write_to`] and used by
+/// `MmapBitSet` files. Blocks are read one at a time, so `reader` should
+/// usually be buffered.
+///
+/// [`BitSet::write_to`]: struct.BitSet.html#method.write_to
+pub struct BlockReader<R, T = usize> {
+  reader: R,
+  num_blocks: usize,
+  remaining: usize,
+  _marker: PhantomData<T>,
+}
+
+impl<R, T> BlockReader<R, T>
+where
+  R: Read,
+  T: PlainBlock,
+{
+  /// Creates a `BlockReader`, reading and validating the header of the
+  /// serialized set.
+  ///
+  /// Returns an error of kind `InvalidData` if the header is not valid for
+  /// blocks of type `T`.
+  pub fn new(mut reader: R) -> io::Result<Self> {
+    let header = Header::read::<T, _>(&mut reader)?;
+    Ok(Self {
+      reader,
+      num_blocks: header.num_blocks as usize,
+      remaining: header.num_blocks as usize,
+      _marker: PhantomData,
+    })
+  }
+
+  /// Returns the total number of blocks of the serialized set.
+  pub fn num_blocks(&self) -> usize {
+    self.num_blocks
+  }
+
+  /// Unwraps the underlying reader.
+  pub fn into_inner(self) -> R {
+    self.reader
+  }
+}
+
+impl<R, T> Iterator for BlockReader<R, T>
+where
+  R: Read,
+  T: PlainBlock,
+{
+  type Item = io::Result<T>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.remaining == 0 {
+      return None;
+    }
+
+    let mut blk = T::zero();
+    // SAFETY: every bit pattern of `T` is valid since `T: PlainBlock`.
+    let buf = unsafe {
+      slice::from_raw_parts_mut(&mut blk as *mut T as *mut u8, mem::size_of::<T>())
+    };
+    match self.reader.read_exact(buf) {
+      Ok(()) => {
+        self.remaining -= 1;
+        Some(Ok(blk))
+      }
+      Err(err) => {
+        self.remaining = 0;
+        Some(Err(err))
+      }
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (0, Some(self.remaining))
+  }
+}
+
+/// A writer of the blocks of a serialized bit set.
+///
+/// The number of blocks is written in the header, so it must be known before
+/// any block is written.
+pub struct BlockWriter<W, T = usize> {
+  writer: W,
+  remaining: usize,
+  _marker: PhantomData<T>,
+}
+
+impl<W, T> BlockWriter<W, T>
+where
+  W: Write,
+  T: PlainBlock,
+{
+  /// Creates a `BlockWriter` for a set of `num_blocks` blocks, writing the
+  /// header of the serialized set.
+  pub fn new(mut writer: W, num_blocks: usize) -> io::Result<Self> {
+    Header::new::<T>(num_blocks).write(&mut writer)?;
+    Ok(Self {
+      writer,
+      remaining: num_blocks,
+      _marker: PhantomData,
+    })
+  }
+
+  /// Writes the next block.
+  ///
+  /// Returns an error of kind `InvalidInput` if all the blocks announced in
+  /// the header have already been written.
+  pub fn write_block(&mut self, blk: T) -> io::Result<()> {
+    self.write_blocks(slice::from_ref(&blk))
+  }
+
+  /// Writes the next blocks.
+  ///
+  /// Returns an error of kind `InvalidInput` if there are more blocks than
+  /// announced in the header.
+  pub fn write_blocks(&mut self, blks: &[T]) -> io::Result<()> {
+    if blks.len() > self.remaining {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "too many blocks written to BlockWriter"));
+    }
+
+    // SAFETY: `T: PlainBlock` has no padding bytes.
+    let buf = unsafe {
+      slice::from_raw_parts(blks.as_ptr() as *const u8, mem::size_of_val(blks))
+    };
+    self.writer.write_all(buf)?;
+    self.remaining -= blks.len();
+    Ok(())
+  }
+
+  /// Finishes writing, returning the underlying writer.
+  ///
+  /// Returns an error of kind `InvalidInput` if fewer blocks than announced in
+  /// the header have been written.
+  pub fn finish(self) -> io::Result<W> {
+    if self.remaining != 0 {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "too few blocks written to BlockWriter"));
+    }
+    Ok(self.writer)
+  }
+}
+
+impl<T> BitSet<T>
+where
+  T: PlainBlock,
+{
+  /// Writes the set to `writer` in the serialized form.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::{BitSet, bitset};
+  ///
+  /// let set1 = bitset![7,3,5,18];
+  /// let mut buf = vec![];
+  /// set1.write_to(&mut buf)?;
+  ///
+  /// assert_eq!(<BitSet>::read_from(&buf[..])?, set1);
+  /// # Ok::<(), std::io::Error>(())
+  /// ```
+  pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+    let blocks = self.as_blocks();
+    let mut writer = BlockWriter::new(writer, blocks.len())?;
+    writer.write_blocks(blocks)?;
+    writer.finish()?;
+    Ok(())
+  }
+
+  /// Reads a set in the serialized form from `reader`.
+  pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+    let blocks = BlockReader::new(reader)?.collect::<io::Result<Vec<T>>>()?;
+    Ok(Self::from_vec(blocks))
+  }
+}
+
+#[derive(Clone, Copy)]
+enum MergeOp {
+  Union,
+  Intersection,
+  Difference,
+  SymmetricDifference,
+}
+
+/// An iterator merging several serialized sets blockwise, producing
+/// `io::Result<T>`s.
+///
+/// The blocks of each input are read only once and no set is materialized,
+/// so merging takes bounded memory regardless of the sizes of the inputs.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::{BitSet, BlockReader, MergeBlocks, bitset};
+///
+/// let mut buf1 = vec![];
+/// let mut buf2 = vec![];
+/// bitset![1,5,130].write_to(&mut buf1)?;
+/// bitset![5,7].write_to(&mut buf2)?;
+///
+/// let readers = vec![
+///   BlockReader::<_, usize>::new(&buf1[..])?,
+///   BlockReader::new(&buf2[..])?,
+/// ];
+/// let mut out = vec![];
+/// MergeBlocks::union(readers).write_to(&mut out)?;
+///
+/// assert_eq!(<BitSet>::read_from(&out[..])?, bitset![1,5,7,130]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct MergeBlocks<R, T = usize> {
+  readers: Vec<BlockReader<R, T>>,
+  op: MergeOp,
+  num_blocks: usize,
+  index: usize,
+}
+
+impl<R, T> MergeBlocks<R, T>
+where
+  R: Read,
+  T: PlainBlock,
+{
+  fn new<I>(readers: I, op: MergeOp) -> Self
+  where
+    I: IntoIterator<Item = BlockReader<R, T>>,
+  {
+    let readers = readers.into_iter().collect::<Vec<_>>();
+    let lens = readers.iter().map(|r| r.num_blocks);
+    let num_blocks = match op {
+      MergeOp::Union | MergeOp::SymmetricDifference => lens.max(),
+      MergeOp::Intersection => lens.min(),
+      MergeOp::Difference => lens.take(1).next(),
+    }.unwrap_or(0);
+
+    Self {
+      readers,
+      op,
+      num_blocks,
+      index: 0,
+    }
+  }
+
+  /// Computes the union of the sets read by `readers`.
+  pub fn union<I>(readers: I) -> Self
+  where
+    I: IntoIterator<Item = BlockReader<R, T>>,
+  {
+    Self::new(readers, MergeOp::Union)
+  }
+
+  /// Computes the intersection of the sets read by `readers`.
+  ///
+  /// The intersection of no sets is the empty set.
+  pub fn intersection<I>(readers: I) -> Self
+  where
+    I: IntoIterator<Item = BlockReader<R, T>>,
+  {
+    Self::new(readers, MergeOp::Intersection)
+  }
+
+  /// Computes the difference of the set read by `base` and the sets read by
+  /// `others`.
+  pub fn difference<I>(base: BlockReader<R, T>, others: I) -> Self
+  where
+    I: IntoIterator<Item = BlockReader<R, T>>,
+  {
+    Self::new(std::iter::once(base).chain(others), MergeOp::Difference)
+  }
+
+  /// Computes the symmetric difference of the sets read by `readers`, i.e.
+  /// the values present in an odd number of the sets.
+  pub fn symmetric_difference<I>(readers: I) -> Self
+  where
+    I: IntoIterator<Item = BlockReader<R, T>>,
+  {
+    Self::new(readers, MergeOp::SymmetricDifference)
+  }
+
+  /// Returns the total number of blocks produced by the merge.
+  pub fn num_blocks(&self) -> usize {
+    self.num_blocks
+  }
+
+  /// Writes the merged set to `writer` in the serialized form, returning the
+  /// writer.
+  pub fn write_to<W: Write>(self, writer: W) -> io::Result<W> {
+    let mut writer = BlockWriter::new(writer, self.num_blocks)?;
+    for blk in self {
+      writer.write_block(blk?)?;
+    }
+    writer.finish()
+  }
+
+  /// Iterates over the elements of the merged set in ascending order,
+  /// producing `io::Result<usize>`s.
+  pub fn elements(self) -> MergeElements<R, T> {
+    MergeElements {
+      blocks: self,
+      blk: T::zero(),
+    }
+  }
+
+  fn next_block(&mut self) -> io::Result<T> {
+    let mut readers = self.readers.iter_mut();
+    let mut acc = match readers.next() {
+      Some(reader) => read_or_zero(reader)?,
+      None => return Ok(T::zero()),
+    };
+
+    for reader in readers {
+      let blk = read_or_zero(reader)?;
+      match self.op {
+        MergeOp::Union => acc = acc.bit_or(blk),
+        MergeOp::Intersection => acc = acc.bit_and(blk),
+        MergeOp::Difference => acc = acc.bit_and(blk.bit_not()),
+        MergeOp::SymmetricDifference => acc = acc.bit_xor(blk),
+      }
+    }
+    Ok(acc)
+  }
+}
+
+/// Reads the next block of `reader`, or a zero block if all its blocks have
+/// been read.
+fn read_or_zero<R: Read, T: PlainBlock>(reader: &mut BlockReader<R, T>) -> io::Result<T> {
+  reader.next().unwrap_or_else(|| Ok(T::zero()))
+}
+
+impl<R, T> Iterator for MergeBlocks<R, T>
+where
+  R: Read,
+  T: PlainBlock,
+{
+  type Item = io::Result<T>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.index == self.num_blocks {
+      return None;
+    }
+
+    match self.next_block() {
+      Ok(blk) => {
+        self.index += 1;
+        Some(Ok(blk))
+      }
+      Err(err) => {
+        self.index = self.num_blocks;
+        Some(Err(err))
+      }
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (0, Some(self.num_blocks - self.index))
+  }
+}
+
+/// An iterator over the elements of sets merged by [`MergeBlocks`], producing
+/// `io::Result<usize>`s.
+///
+/// This struct is created by the [`elements`] method on [`MergeBlocks`].
+///
+/// [`MergeBlocks`]: struct.MergeBlocks.html
+/// [`elements`]: struct.MergeBlocks.html#method.elements
+pub struct MergeElements<R, T = usize> {
+  blocks: MergeBlocks<R, T>,
+  blk: T,
+}
+
+impl<R, T> Iterator for MergeElements<R, T>
+where
+  R: Read,
+  T: PlainBlock,
+{
+  type Item = io::Result<usize>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while self.blk == T::zero() {
+      match self.blocks.next()? {
+        Ok(blk) => self.blk = blk,
+        Err(err) => return Some(Err(err)),
+      }
+    }
+
+    let bit = self.blk.trailing_zeros() as usize;
+    self.blk = self.blk.bit_xor(T::one().shl_bits(bit));
+    Some(Ok((self.blocks.index - 1) * T::NUM_BITS + bit))
+  }
+}
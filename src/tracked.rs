@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::iter::FromIterator;
+use std::slice;
+use crate::{BitBlock, BitSet, Iter};
+
+/// A `BitSet` which remembers its elements, so that clearing takes time
+/// proportional to their number rather than to the size of the underlying bit
+/// vector, which is kept zeroed for reuse.
+///
+/// Like the sparse sets of Briggs and Torczon, the elements are also kept in a
+/// dense list in insertion order, with a map of their positions so that
+/// removing an element only marks its entry as removed. The list gives
+/// iteration in insertion order and tells which blocks to clear.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::TrackedBitSet;
+///
+/// let mut set = <TrackedBitSet>::new();
+/// set.insert(1_000_000);
+/// set.insert(7);
+/// set.insert(42);
+/// set.remove(7);
+///
+/// assert_eq!(set.iter_inserted().collect::<Vec<_>>(), vec![1_000_000, 42]);
+/// assert_eq!(set.iter().collect::<Vec<_>>(), vec![42, 1_000_000]);
+/// assert_eq!(set.as_bitset().intersection_len(&[42].iter().cloned().collect()), 1);
+///
+/// set.clear();
+/// assert!(set.is_empty());
+/// ```
+#[derive(Clone, Default)]
+pub struct TrackedBitSet<T = usize> {
+  set: BitSet<T>,
+  /// The elements in insertion order, with `None` for the removed ones.
+  order: Vec<Option<usize>>,
+  /// The position in `order` of each element.
+  positions: HashMap<usize, usize>,
+}
+
+impl<T> TrackedBitSet<T>
+where
+  T: BitBlock,
+{
+  /// Creates a new empty `TrackedBitSet`.
+  pub fn new() -> Self {
+    Self {
+      set: BitSet::new(),
+      order: vec![],
+      positions: HashMap::new(),
+    }
+  }
+
+  /// Creates a new empty `TrackedBitSet` with the given capacity for the
+  /// underlying bit vector.
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      set: BitSet::with_capacity(capacity),
+      order: vec![],
+      positions: HashMap::new(),
+    }
+  }
+
+  /// Returns the underlying set, which provides the queries of `BitSet`.
+  pub fn as_bitset(&self) -> &BitSet<T> {
+    &self.set
+  }
+
+  /// Iterates over the set, producing the elements in ascending order.
+  ///
+  /// Note that this takes time proportional to the largest element inserted
+  /// since the set was created.
+  pub fn iter(&self) -> Iter<'_, T> {
+    self.set.iter()
+  }
+
+  /// Iterates over the set, producing the elements in the order they were
+  /// inserted.
+  pub fn iter_inserted(&self) -> IterInserted<'_> {
+    IterInserted {
+      iter: self.order.iter(),
+    }
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.positions.len()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.positions.is_empty()
+  }
+
+  /// Clear the set, removing all elements, in time proportional to their
+  /// number.
+  pub fn clear(&mut self) {
+    self.set.clear_blocks_of(self.order.drain(..).flatten());
+    self.positions.clear();
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.set.contains(value)
+  }
+
+  /// Adds a value to the set. Returns whether the value was absent from the
+  /// set.
+  pub fn insert(&mut self, value: usize) -> bool {
+    if !self.set.insert(value) {
+      return false;
+    }
+    self.positions.insert(value, self.order.len());
+    self.order.push(Some(value));
+    true
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  ///
+  /// The block of the value is kept even if it becomes zero.
+  pub fn remove(&mut self, value: usize) -> bool {
+    if !self.set.remove_in_place(value) {
+      return false;
+    }
+
+    let index = self.positions.remove(&value).unwrap();
+    self.order[index] = None;
+    // Drop the removed entries once they outnumber the elements, so that the
+    // list stays proportional to the set.
+    if self.order.len() > 2 * self.positions.len() + 16 {
+      self.compact();
+    }
+    true
+  }
+
+  fn compact(&mut self) {
+    let mut len = 0;
+    for i in 0..self.order.len() {
+      if let Some(value) = self.order[i] {
+        self.order[len] = Some(value);
+        self.positions.insert(value, len);
+        len += 1;
+      }
+    }
+    self.order.truncate(len);
+  }
+}
+
+impl<T> Debug for TrackedBitSet<T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.set.fmt(f)
+  }
+}
+
+impl<T: BitBlock> PartialEq for TrackedBitSet<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.set == other.set
+  }
+}
+
+impl<T: BitBlock> Eq for TrackedBitSet<T> {}
+
+impl<'a, T> IntoIterator for &'a TrackedBitSet<T>
+where
+  T: BitBlock,
+{
+  type IntoIter = Iter<'a, T>;
+  type Item = usize;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<T> FromIterator<usize> for TrackedBitSet<T>
+where
+  T: BitBlock,
+{
+  fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+    let mut set = Self::new();
+    set.extend(iter);
+    set
+  }
+}
+
+impl<T> Extend<usize> for TrackedBitSet<T>
+where
+  T: BitBlock,
+{
+  fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+    for x in iter {
+      self.insert(x);
+    }
+  }
+}
+
+/// An iterator over the elements of a `TrackedBitSet` in insertion order.
+///
+/// This struct is created by the [`iter_inserted`] method on
+/// [`TrackedBitSet`]s.
+///
+/// [`TrackedBitSet`]: struct.TrackedBitSet.html
+/// [`iter_inserted`]: struct.TrackedBitSet.html#method.iter_inserted
+pub struct IterInserted<'a> {
+  iter: slice::Iter<'a, Option<usize>>,
+}
+
+impl<'a> Iterator for IterInserted<'a> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    self.iter.find_map(|&value| value)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (0, self.iter.size_hint().1)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn clear_keeps_blocks() {
+    let mut set = <TrackedBitSet<u8>>::new();
+    set.extend(vec![3, 500, 1000]);
+    let nblks = set.set.vec.len();
+    set.clear();
+
+    assert!(set.is_empty());
+    assert_eq!(set.set.vec.len(), nblks);
+    assert_eq!(set.iter().count(), 0);
+    assert_eq!(*set.as_bitset(), BitSet::new());
+    assert!(set.insert(500));
+    assert_eq!(set.iter_inserted().collect::<Vec<_>>(), vec![500]);
+  }
+
+  #[test]
+  fn remove_keeps_blocks() {
+    let mut set = <TrackedBitSet<u8>>::new();
+    set.extend(vec![3, 1000]);
+    let nblks = set.set.vec.len();
+    set.remove(1000);
+
+    assert_eq!(set.set.vec.len(), nblks);
+    assert_eq!(set.iter_inserted().collect::<Vec<_>>(), vec![3]);
+  }
+}
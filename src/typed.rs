@@ -0,0 +1,349 @@
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops::{
+  BitOr, BitOrAssign, BitAnd, BitAndAssign, Sub, SubAssign, BitXor, BitXorAssign
+};
+use crate::{BitBlock, BitSet, Iter};
+
+/// A trait for types used as the elements of a [`TypedBitSet`], typically
+/// newtypes of indices.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::BitIndex;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct NodeId(u32);
+///
+/// impl BitIndex for NodeId {
+///   fn to_usize(self) -> usize {
+///     self.0 as usize
+///   }
+///
+///   fn from_usize(index: usize) -> Self {
+///     NodeId(index as u32)
+///   }
+/// }
+///
+/// assert_eq!(NodeId::from_usize(NodeId(7).to_usize()), NodeId(7));
+/// ```
+///
+/// [`TypedBitSet`]: struct.TypedBitSet.html
+pub trait BitIndex: Copy {
+  /// Converts the value to the index of its bit.
+  fn to_usize(self) -> usize;
+
+  /// Converts the index of a bit back to the value. Only called with indices
+  /// returned by `to_usize`.
+  fn from_usize(index: usize) -> Self;
+}
+
+macro_rules! impl_bit_index {
+  ($($t:ty),*) => {
+    $(
+      impl BitIndex for $t {
+        #[inline]
+        fn to_usize(self) -> usize {
+          usize::try_from(self).expect("index out of range")
+        }
+
+        #[inline]
+        fn from_usize(index: usize) -> Self {
+          <$t>::try_from(index).expect("index out of range")
+        }
+      }
+    )*
+  };
+}
+
+impl_bit_index!(u8, u16, u32, u64, usize);
+
+/// A `BitSet` whose elements are of the typed index `I` instead of `usize`.
+///
+/// It is a thin wrapper around `BitSet<T>`, so that sets of different kinds
+/// of indices can not be mixed up.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::{BitIndex, TypedBitSet};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct NodeId(usize);
+///
+/// impl BitIndex for NodeId {
+///   fn to_usize(self) -> usize { self.0 }
+///   fn from_usize(index: usize) -> Self { NodeId(index) }
+/// }
+///
+/// let mut set1 = <TypedBitSet<NodeId>>::new();
+/// set1.insert(NodeId(3));
+/// set1.insert(NodeId(42));
+///
+/// let set2 = [NodeId(3), NodeId(5)].iter().cloned().collect::<TypedBitSet<NodeId>>();
+///
+/// assert!(set1.contains(NodeId(42)));
+/// assert_eq!((&set1 & &set2).iter().collect::<Vec<_>>(), vec![NodeId(3)]);
+/// assert_eq!(format!("{:?}", set2), "{NodeId(3), NodeId(5)}");
+/// ```
+pub struct TypedBitSet<I, T = usize> {
+  set: BitSet<T>,
+  marker: PhantomData<fn(I) -> I>,
+}
+
+impl<I, T> TypedBitSet<I, T>
+where
+  I: BitIndex,
+  T: BitBlock,
+{
+  /// Creates a new empty `TypedBitSet`.
+  pub fn new() -> Self {
+    Self::from_bitset(BitSet::new())
+  }
+
+  /// Creates a new empty `TypedBitSet` with the given capacity for the
+  /// underlying bit vector.
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self::from_bitset(BitSet::with_capacity(capacity))
+  }
+
+  /// Creates a `TypedBitSet` from the untyped `set`.
+  pub fn from_bitset(set: BitSet<T>) -> Self {
+    Self {
+      set,
+      marker: PhantomData,
+    }
+  }
+
+  /// Returns the underlying untyped set.
+  pub fn as_bitset(&self) -> &BitSet<T> {
+    &self.set
+  }
+
+  /// Converts the set into the underlying untyped set.
+  pub fn into_bitset(self) -> BitSet<T> {
+    self.set
+  }
+
+  /// Iterates over the set, producing the elements in ascending order of
+  /// their indices.
+  pub fn iter(&self) -> TypedIter<'_, I, T> {
+    TypedIter {
+      iter: self.set.iter(),
+      marker: PhantomData,
+    }
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.set.len()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.set.is_empty()
+  }
+
+  /// Clear the set, removing all elements.
+  pub fn clear(&mut self) {
+    self.set.clear();
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: I) -> bool {
+    self.set.contains(value.to_usize())
+  }
+
+  /// Adds a value to the set. Returns whether the value was absent from the
+  /// set.
+  pub fn insert(&mut self, value: I) -> bool {
+    self.set.insert(value.to_usize())
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in
+  /// the set.
+  pub fn remove(&mut self, value: I) -> bool {
+    self.set.remove(value.to_usize())
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  pub fn is_subset(&self, other: &Self) -> bool {
+    self.set.is_subset(&other.set)
+  }
+
+  /// Returns whether the set is a proper subset of `other`.
+  pub fn is_proper_subset(&self, other: &Self) -> bool {
+    self.set.is_proper_subset(&other.set)
+  }
+}
+
+impl<I, T> Clone for TypedBitSet<I, T>
+where
+  T: BitBlock,
+{
+  fn clone(&self) -> Self {
+    Self {
+      set: self.set.clone(),
+      marker: PhantomData,
+    }
+  }
+}
+
+impl<I, T> Default for TypedBitSet<I, T>
+where
+  T: BitBlock,
+{
+  fn default() -> Self {
+    Self {
+      set: BitSet::default(),
+      marker: PhantomData,
+    }
+  }
+}
+
+impl<I, T> PartialEq for TypedBitSet<I, T>
+where
+  T: BitBlock,
+{
+  fn eq(&self, other: &Self) -> bool {
+    self.set == other.set
+  }
+}
+
+impl<I, T> Eq for TypedBitSet<I, T>
+where
+  T: BitBlock,
+{}
+
+impl<I, T> Hash for TypedBitSet<I, T>
+where
+  T: BitBlock,
+{
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.set.hash(state);
+  }
+}
+
+impl<I, T> Debug for TypedBitSet<I, T>
+where
+  I: BitIndex + Debug,
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_set().entries(self).finish()
+  }
+}
+
+impl<I, T> From<BitSet<T>> for TypedBitSet<I, T>
+where
+  I: BitIndex,
+  T: BitBlock,
+{
+  fn from(set: BitSet<T>) -> Self {
+    Self::from_bitset(set)
+  }
+}
+
+impl<'a, I, T> IntoIterator for &'a TypedBitSet<I, T>
+where
+  I: BitIndex,
+  T: BitBlock,
+{
+  type IntoIter = TypedIter<'a, I, T>;
+  type Item = I;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<I, T> FromIterator<I> for TypedBitSet<I, T>
+where
+  I: BitIndex,
+  T: BitBlock,
+{
+  fn from_iter<It: IntoIterator<Item = I>>(iter: It) -> Self {
+    let mut set = Self::new();
+    set.extend(iter);
+    set
+  }
+}
+
+impl<I, T> Extend<I> for TypedBitSet<I, T>
+where
+  I: BitIndex,
+  T: BitBlock,
+{
+  fn extend<It: IntoIterator<Item = I>>(&mut self, iter: It) {
+    self.set.extend(iter.into_iter().map(I::to_usize));
+  }
+}
+
+macro_rules! typed_op_impl {
+  ($name:ident, $method:ident, $assign_name:ident, $assign_method:ident) => {
+    impl<I, T: BitBlock> $name<TypedBitSet<I, T>> for TypedBitSet<I, T> {
+      type Output = TypedBitSet<I, T>;
+
+      fn $method(self, rhs: TypedBitSet<I, T>) -> TypedBitSet<I, T> {
+        TypedBitSet {
+          set: self.set.$method(rhs.set),
+          marker: PhantomData,
+        }
+      }
+    }
+
+    impl<'a, I, T: BitBlock> $name<&'a TypedBitSet<I, T>> for &'a TypedBitSet<I, T> {
+      type Output = TypedBitSet<I, T>;
+
+      fn $method(self, rhs: &'a TypedBitSet<I, T>) -> TypedBitSet<I, T> {
+        TypedBitSet {
+          set: (&self.set).$method(&rhs.set),
+          marker: PhantomData,
+        }
+      }
+    }
+
+    impl<I, T: BitBlock> $assign_name<TypedBitSet<I, T>> for TypedBitSet<I, T> {
+      fn $assign_method(&mut self, rhs: TypedBitSet<I, T>) {
+        self.set.$assign_method(rhs.set);
+      }
+    }
+
+    impl<'a, I, T: BitBlock> $assign_name<&'a TypedBitSet<I, T>> for TypedBitSet<I, T> {
+      fn $assign_method(&mut self, rhs: &'a TypedBitSet<I, T>) {
+        self.set.$assign_method(&rhs.set);
+      }
+    }
+  };
+}
+
+typed_op_impl!(BitOr, bitor, BitOrAssign, bitor_assign);
+typed_op_impl!(BitAnd, bitand, BitAndAssign, bitand_assign);
+typed_op_impl!(Sub, sub, SubAssign, sub_assign);
+typed_op_impl!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+/// An iterator for `TypedBitSet`.
+///
+/// This struct is created by the [`iter`] method on [`TypedBitSet`]s.
+///
+/// [`TypedBitSet`]: struct.TypedBitSet.html
+/// [`iter`]: struct.TypedBitSet.html#method.iter
+pub struct TypedIter<'a, I, T> {
+  iter: Iter<'a, T>,
+  marker: PhantomData<fn() -> I>,
+}
+
+impl<'a, I, T> Iterator for TypedIter<'a, I, T>
+where
+  I: BitIndex,
+  T: BitBlock,
+{
+  type Item = I;
+
+  fn next(&mut self) -> Option<I> {
+    self.iter.next().map(I::from_usize)
+  }
+}
@@ -0,0 +1,359 @@
+use std::fmt::{self, Debug, Formatter};
+use std::mem;
+use std::ops::{BitOr, BitAnd, Sub, BitXor};
+use std::slice;
+use crate::{BitBlock, BitSet, Iter, PlainBlock};
+
+/// A read-only view of a bit set stored in a borrowed slice of blocks.
+///
+/// The bit indexed by `i` is stored in the block `i / T::NUM_BITS` at the bit
+/// position `i % T::NUM_BITS`, which is the same layout as `BitSet`. Unlike
+/// `BitSet`, the slice may have trailing zero blocks.
+///
+/// The set operators `|`, `&`, `-` and `^` are available between views and
+/// produce owned `BitSet`s.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::{BitSet, BitSetRef};
+///
+/// let blocks = [0b1010u8, 0, 0b1];
+/// let view = BitSetRef::new(&blocks);
+/// let set = [2, 3].iter().cloned().collect::<BitSet<u8>>();
+///
+/// assert_eq!(view.iter().collect::<Vec<_>>(), vec![1, 3, 16]);
+/// assert_eq!((view - set.as_view()).iter().collect::<Vec<_>>(), vec![1, 16]);
+/// ```
+pub struct BitSetRef<'a, T = usize> {
+  slice: &'a [T],
+}
+
+impl<'a, T> Clone for BitSetRef<'a, T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<'a, T> Copy for BitSetRef<'a, T> {}
+
+impl<'a, T> BitSetRef<'a, T>
+where
+  T: BitBlock,
+{
+  /// Creates a view of the bit set stored in `slice`.
+  pub fn new(slice: &'a [T]) -> Self {
+    Self { slice }
+  }
+
+  /// Returns the underlying slice of blocks.
+  pub fn as_slice(&self) -> &'a [T] {
+    self.slice
+  }
+
+  /// Returns the number of bits (set & unset) covered by the view.
+  pub fn num_bits(&self) -> usize {
+    self.slice.len() * T::NUM_BITS
+  }
+
+  /// Iterates over the view, producing `usize`s representing the elements in
+  /// the set, in ascending order.
+  pub fn iter(&self) -> Iter<'a, T> {
+    Iter::from_slice(self.slice, self.num_bits())
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.slice.iter().map(|x| x.count_ones() as usize).sum()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.slice.iter().all(|x| *x == T::zero())
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.slice.get(value / T::NUM_BITS)
+      .is_some_and(|x| x.bit_and(T::one().shl_bits(value % T::NUM_BITS)) != T::zero())
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::BitSetRef;
+  ///
+  /// let blocks1 = [0b0010u8, 0];
+  /// let blocks2 = [0b0110u8];
+  ///
+  /// assert!(BitSetRef::new(&blocks1).is_subset(&BitSetRef::new(&blocks2)));
+  /// assert!(!BitSetRef::new(&blocks2).is_subset(&BitSetRef::new(&blocks1)));
+  /// ```
+  pub fn is_subset(&self, other: &BitSetRef<'_, T>) -> bool {
+    self.slice.iter().enumerate().all(|(i, &x)| {
+      x.bit_and(other.slice.get(i).copied().unwrap_or_else(T::zero).bit_not()) == T::zero()
+    })
+  }
+
+  /// Copies the view into an owned `BitSet`.
+  pub fn to_bitset(&self) -> BitSet<T> {
+    BitSet::from_vec(self.slice.to_vec())
+  }
+
+  /// Combines two views blockwise with `f`, with missing blocks treated as
+  /// zeros.
+  fn combine<F>(&self, other: &BitSetRef<'_, T>, f: F) -> BitSet<T>
+  where
+    F: Fn(T, T) -> T,
+  {
+    let nblks = self.slice.len().max(other.slice.len());
+    let vec = (0..nblks)
+      .map(|i| f(
+        self.slice.get(i).copied().unwrap_or_else(T::zero),
+        other.slice.get(i).copied().unwrap_or_else(T::zero)))
+      .collect();
+    BitSet::from_vec(vec)
+  }
+}
+
+impl<'a, T> BitSetRef<'a, T>
+where
+  T: PlainBlock,
+{
+  /// Creates a view of the bit set stored in `bytes`, in the native byte order
+  /// of `T`.
+  ///
+  /// Returns `None` if `bytes` is not properly aligned for `T`, or if the
+  /// length of `bytes` is not a multiple of the size of `T`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use bittyset::BitSetRef;
+  ///
+  /// let bytes = [0b1001u8, 0b1];
+  /// let view = BitSetRef::<u8>::from_bytes(&bytes).unwrap();
+  ///
+  /// assert_eq!(view.iter().collect::<Vec<_>>(), vec![0, 3, 8]);
+  /// ```
+  pub fn from_bytes(bytes: &'a [u8]) -> Option<Self> {
+    if !is_valid_layout::<T>(bytes) {
+      return None;
+    }
+
+    // SAFETY: `bytes` is aligned and sized for `T`, and every bit pattern of
+    // `T` is valid since `T: PlainBlock`.
+    let slice = unsafe {
+      slice::from_raw_parts(
+        bytes.as_ptr() as *const T,
+        bytes.len() / mem::size_of::<T>())
+    };
+    Some(Self::new(slice))
+  }
+}
+
+impl<'a, T> Debug for BitSetRef<'a, T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_set().entries(self.iter()).finish()
+  }
+}
+
+impl<'a, T> IntoIterator for BitSetRef<'a, T>
+where
+  T: BitBlock,
+{
+  type IntoIter = Iter<'a, T>;
+  type Item = usize;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<'a, T> From<BitSetRef<'a, T>> for BitSet<T>
+where
+  T: BitBlock,
+{
+  fn from(view: BitSetRef<'a, T>) -> Self {
+    view.to_bitset()
+  }
+}
+
+/// A mutable view of a bit set stored in a borrowed slice of blocks.
+///
+/// The layout is the same as [`BitSetRef`]. Since the slice cannot grow, the
+/// view can only hold values less than [`num_bits`].
+///
+/// [`BitSetRef`]: struct.BitSetRef.html
+/// [`num_bits`]: struct.BitSetMut.html#method.num_bits
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::BitSetMut;
+///
+/// let mut blocks = [0u8; 2];
+/// let mut view = BitSetMut::new(&mut blocks);
+///
+/// assert!(view.insert(9));
+/// assert!(view.contains(9));
+/// assert_eq!(blocks, [0, 0b10]);
+/// ```
+pub struct BitSetMut<'a, T = usize> {
+  slice: &'a mut [T],
+}
+
+impl<'a, T> BitSetMut<'a, T>
+where
+  T: BitBlock,
+{
+  /// Creates a mutable view of the bit set stored in `slice`.
+  pub fn new(slice: &'a mut [T]) -> Self {
+    Self { slice }
+  }
+
+  /// Returns a read-only view of the set.
+  pub fn as_view(&self) -> BitSetRef<'_, T> {
+    BitSetRef::new(self.slice)
+  }
+
+  /// Returns the number of bits (set & unset) covered by the view.
+  pub fn num_bits(&self) -> usize {
+    self.as_view().num_bits()
+  }
+
+  /// Iterates over the view, producing `usize`s representing the elements in
+  /// the set, in ascending order.
+  pub fn iter(&self) -> Iter<'_, T> {
+    self.as_view().iter()
+  }
+
+  /// Returns the number of elements in the set.
+  pub fn len(&self) -> usize {
+    self.as_view().len()
+  }
+
+  /// Returns whether the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.as_view().is_empty()
+  }
+
+  /// Returns whether the given `value` is present in the set.
+  pub fn contains(&self, value: usize) -> bool {
+    self.as_view().contains(value)
+  }
+
+  /// Returns whether the set is a subset of `other`.
+  pub fn is_subset(&self, other: &BitSetRef<'_, T>) -> bool {
+    self.as_view().is_subset(other)
+  }
+
+  /// Copies the view into an owned `BitSet`.
+  pub fn to_bitset(&self) -> BitSet<T> {
+    self.as_view().to_bitset()
+  }
+
+  /// Adds a value to the set. Returns whether the value was not present in the
+  /// set.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `value` is not less than [`num_bits`].
+  ///
+  /// [`num_bits`]: struct.BitSetMut.html#method.num_bits
+  pub fn insert(&mut self, value: usize) -> bool {
+    assert!(value < self.num_bits(), "value out of bounds of the view");
+    let blk = &mut self.slice[value / T::NUM_BITS];
+    let mask = T::one().shl_bits(value % T::NUM_BITS);
+    let present = blk.bit_and(mask) != T::zero();
+    *blk = blk.bit_or(mask);
+    !present
+  }
+
+  /// Removes a value from the set. Returns whether the value was present in the set.
+  pub fn remove(&mut self, value: usize) -> bool {
+    match self.slice.get_mut(value / T::NUM_BITS) {
+      Some(blk) => {
+        let mask = T::one().shl_bits(value % T::NUM_BITS);
+        let present = blk.bit_and(mask) != T::zero();
+        *blk = blk.bit_and(mask.bit_not());
+        present
+      }
+      None => false,
+    }
+  }
+
+  /// Clear the set, removing all elements.
+  pub fn clear(&mut self) {
+    self.slice.fill(T::zero());
+  }
+}
+
+impl<'a, T> BitSetMut<'a, T>
+where
+  T: PlainBlock,
+{
+  /// Creates a mutable view of the bit set stored in `bytes`, in the native
+  /// byte order of `T`.
+  ///
+  /// Returns `None` if `bytes` is not properly aligned for `T`, or if the
+  /// length of `bytes` is not a multiple of the size of `T`.
+  pub fn from_bytes(bytes: &'a mut [u8]) -> Option<Self> {
+    if !is_valid_layout::<T>(bytes) {
+      return None;
+    }
+
+    // SAFETY: `bytes` is aligned and sized for `T`, and every bit pattern of
+    // `T` is valid since `T: PlainBlock`.
+    let slice = unsafe {
+      slice::from_raw_parts_mut(
+        bytes.as_mut_ptr() as *mut T,
+        bytes.len() / mem::size_of::<T>())
+    };
+    Some(Self::new(slice))
+  }
+}
+
+impl<'a, T> Debug for BitSetMut<'a, T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.as_view().fmt(f)
+  }
+}
+
+fn is_valid_layout<T>(bytes: &[u8]) -> bool {
+  (bytes.as_ptr() as usize).is_multiple_of(mem::align_of::<T>()) &&
+    bytes.len().is_multiple_of(mem::size_of::<T>())
+}
+
+macro_rules! view_op_impl {
+  ( $name:ident, $method:ident, |$x:ident, $y:ident| $body:expr ) => {
+    impl<'a, 'b, T: BitBlock> $name<BitSetRef<'b, T>> for BitSetRef<'a, T> {
+      type Output = BitSet<T>;
+
+      fn $method(self, rhs: BitSetRef<'b, T>) -> BitSet<T> {
+        self.combine(&rhs, |$x, $y| $body)
+      }
+    }
+
+    impl<'a, 'b, 'c, 'd, T: BitBlock> $name<&'c BitSetMut<'d, T>> for &'a BitSetMut<'b, T> {
+      type Output = BitSet<T>;
+
+      fn $method(self, rhs: &'c BitSetMut<'d, T>) -> BitSet<T> {
+        self.as_view().combine(&rhs.as_view(), |$x, $y| $body)
+      }
+    }
+  };
+}
+
+view_op_impl!(BitOr, bitor, |x, y| x.bit_or(y));
+view_op_impl!(BitAnd, bitand, |x, y| x.bit_and(y));
+view_op_impl!(Sub, sub, |x, y| x.bit_and(y.bit_not()));
+view_op_impl!(BitXor, bitxor, |x, y| x.bit_xor(y));
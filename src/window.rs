@@ -0,0 +1,230 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use crate::BitBlock;
+
+/// A set of the sequence numbers seen in a window of the last `size` sequence
+/// numbers, for replay protection and selective acknowledgements.
+///
+/// The window is `start..end`, where `end` is one more than the largest
+/// sequence number advanced to, and `start` is `size` below `end`. The bits are
+/// stored in a ring of blocks, so advancing the window takes amortized
+/// constant time per sequence number.
+///
+/// # Examples
+///
+/// ```
+/// use bittyset::{Mark, SlidingWindow, TooOld};
+///
+/// let mut window = <SlidingWindow>::new(100);
+///
+/// assert_eq!(window.mark(1000), Ok(Mark::Fresh));
+/// assert_eq!(window.mark(1000), Ok(Mark::Duplicate));
+/// assert_eq!(window.mark(998), Ok(Mark::Fresh));
+/// assert_eq!(window.mark(900), Err(TooOld { seq: 900, start: 901 }));
+///
+/// window.advance_to(1003);
+/// let missing = window.missing().filter(|&seq| seq > 995).collect::<Vec<_>>();
+/// assert_eq!(missing, vec![996, 997, 999, 1001, 1002, 1003]);
+/// assert_eq!(window.missing().count(), 98);
+/// ```
+#[derive(Clone)]
+pub struct SlidingWindow<T = usize> {
+  ring: Vec<T>,
+  size: u64,
+  end: u64,
+}
+
+/// The result of [`SlidingWindow::mark`] for sequence numbers in the window.
+///
+/// [`SlidingWindow::mark`]: struct.SlidingWindow.html#method.mark
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+  /// The sequence number was not seen before.
+  Fresh,
+  /// The sequence number was already marked.
+  Duplicate,
+}
+
+/// The error returned by [`SlidingWindow::mark`] for sequence numbers below
+/// the window.
+///
+/// [`SlidingWindow::mark`]: struct.SlidingWindow.html#method.mark
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TooOld {
+  /// The sequence number marked.
+  pub seq: u64,
+  /// The first sequence number of the window.
+  pub start: u64,
+}
+
+impl Display for TooOld {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "sequence number {} before the window starting at {}", self.seq, self.start)
+  }
+}
+
+impl Error for TooOld {}
+
+impl<T> SlidingWindow<T>
+where
+  T: BitBlock,
+{
+  /// Creates an empty window of `size` sequence numbers, ending at zero.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `size` is zero.
+  pub fn new(size: usize) -> Self {
+    assert!(size > 0, "window size must be positive");
+    Self {
+      ring: vec![T::zero(); size.div_ceil(T::NUM_BITS)],
+      size: size as u64,
+      end: 0,
+    }
+  }
+
+  /// Returns the number of sequence numbers in the window.
+  pub fn size(&self) -> usize {
+    self.size as usize
+  }
+
+  /// Returns the first sequence number of the window.
+  pub fn start(&self) -> u64 {
+    self.end.saturating_sub(self.size)
+  }
+
+  /// Returns one more than the last sequence number of the window.
+  pub fn end(&self) -> u64 {
+    self.end
+  }
+
+  /// Returns whether `seq` is in the window and marked.
+  pub fn contains(&self, seq: u64) -> bool {
+    seq >= self.start() && seq < self.end &&
+      self.ring[self.block_index(seq)].bit_and(bit::<T>(seq)) != T::zero()
+  }
+
+  /// Marks `seq` as seen, advancing the window to it if it is past the end.
+  /// Returns whether it was seen before, or an error if it is below the
+  /// window.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `seq` is `u64::MAX`.
+  pub fn mark(&mut self, seq: u64) -> Result<Mark, TooOld> {
+    if seq < self.start() {
+      return Err(TooOld { seq, start: self.start() });
+    }
+    self.advance_to(seq);
+
+    let index = self.block_index(seq);
+    let blk = &mut self.ring[index];
+    let present = blk.bit_and(bit::<T>(seq)) != T::zero();
+    *blk = blk.bit_or(bit::<T>(seq));
+    Ok(if present { Mark::Duplicate } else { Mark::Fresh })
+  }
+
+  /// Advances the window so that `seq` is its last sequence number, forgetting
+  /// the sequence numbers falling out of the window. Does nothing if `seq` is
+  /// already below the end of the window.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `seq` is `u64::MAX`.
+  pub fn advance_to(&mut self, seq: u64) {
+    let end = seq.checked_add(1).expect("sequence number overflow");
+    if end <= self.end {
+      return;
+    }
+
+    let ring_bits = (self.ring.len() * T::NUM_BITS) as u64;
+    if end - self.end >= ring_bits {
+      self.ring.iter_mut().for_each(|blk| *blk = T::zero());
+    } else {
+      // The slots of the new sequence numbers hold the ones a whole ring
+      // before, which are below the window.
+      let mut pos = self.end;
+      while pos < end {
+        let offset = (pos % T::NUM_BITS as u64) as usize;
+        let nbits = (T::NUM_BITS - offset).min((end - pos) as usize);
+        let mask = T::zero().bit_not().shr_bits(T::NUM_BITS - nbits).shl_bits(offset);
+        let index = self.block_index(pos);
+        self.ring[index] = self.ring[index].bit_and(mask.bit_not());
+        pos += nbits as u64;
+      }
+    }
+    self.end = end;
+  }
+
+  /// Iterates over the sequence numbers in the window which are not marked,
+  /// in ascending order.
+  pub fn missing(&self) -> Missing<'_, T> {
+    Missing {
+      window: self,
+      next: self.start(),
+    }
+  }
+
+  #[inline(always)]
+  fn block_index(&self, seq: u64) -> usize {
+    (seq / T::NUM_BITS as u64 % self.ring.len() as u64) as usize
+  }
+}
+
+#[inline(always)]
+fn bit<T: BitBlock>(seq: u64) -> T {
+  T::one().shl_bits((seq % T::NUM_BITS as u64) as usize)
+}
+
+impl<T> Debug for SlidingWindow<T>
+where
+  T: BitBlock,
+{
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_struct("SlidingWindow")
+      .field("start", &self.start())
+      .field("end", &self.end)
+      .field("size", &self.size)
+      .finish()
+  }
+}
+
+/// An iterator over the sequence numbers missing from a `SlidingWindow`.
+///
+/// This struct is created by the [`missing`] method on [`SlidingWindow`]s.
+///
+/// [`SlidingWindow`]: struct.SlidingWindow.html
+/// [`missing`]: struct.SlidingWindow.html#method.missing
+pub struct Missing<'a, T> {
+  window: &'a SlidingWindow<T>,
+  next: u64,
+}
+
+impl<'a, T> Iterator for Missing<'a, T>
+where
+  T: BitBlock,
+{
+  type Item = u64;
+
+  fn next(&mut self) -> Option<u64> {
+    while self.next < self.window.end {
+      let offset = (self.next % T::NUM_BITS as u64) as usize;
+      let blk = self.window.ring[self.window.block_index(self.next)].bit_not().shr_bits(offset);
+
+      if blk == T::zero() {
+        self.next += (T::NUM_BITS - offset) as u64;
+        continue;
+      }
+
+      let seq = self.next + blk.trailing_zeros() as u64;
+      if seq >= self.window.end {
+        break;
+      }
+      self.next = seq + 1;
+      return Some(seq);
+    }
+
+    self.next = self.window.end;
+    None
+  }
+}
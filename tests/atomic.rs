@@ -0,0 +1,66 @@
+use bittyset::{AtomicBitSet, BitSet};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+#[test]
+fn insert_remove() {
+  let set = AtomicBitSet::<u32>::with_capacity(100);
+
+  assert_eq!(set.capacity(), 128);
+  assert!(set.is_empty());
+  assert!(set.insert(7));
+  assert!(!set.insert(7));
+  assert!(set.insert_ordered(127, Ordering::Relaxed));
+  assert!(set.contains(7));
+  assert!(set.contains_ordered(127, Ordering::Relaxed));
+  assert!(set.contains_ordered(127, Ordering::Release));
+  assert!(set.contains_ordered(127, Ordering::AcqRel));
+  assert!(!set.contains(8));
+  assert!(!set.contains(1000));
+  assert!(set.remove(7));
+  assert!(!set.remove(7));
+  assert!(!set.remove(1000));
+  assert_eq!(set.len(), 1);
+  assert_eq!(format!("{:?}", set), "{127}");
+
+  set.clear();
+  assert!(set.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn insert_out_of_capacity() {
+  AtomicBitSet::<u8>::with_capacity(8).insert(8);
+}
+
+#[quickcheck]
+fn snapshot_prop(vec: Vec<u16>) -> bool {
+  let set = vec.into_iter().map(|x| x as usize).collect::<BitSet<u64>>();
+  let atomic = AtomicBitSet::from_bitset(&set, 100);
+
+  atomic.to_bitset() == set &&
+    atomic.iter().eq(set.iter()) &&
+    atomic.len() == set.len()
+}
+
+#[test]
+fn concurrent_insert() {
+  let set = AtomicBitSet::<u64>::with_capacity(100000);
+  let fresh = thread::scope(|s| {
+    let handles = (0..8)
+      .map(|t| {
+        let set = &set;
+        s.spawn(move || {
+          (0..100000).filter(|i| i % (t + 1) == 0).filter(|&i| set.insert(i)).count()
+        })
+      })
+      .collect::<Vec<_>>();
+    handles.into_iter().map(|h| h.join().unwrap()).sum::<usize>()
+  });
+
+  assert_eq!(fresh, 100000);
+  assert_eq!(set.len(), 100000);
+  assert_eq!(set.to_bitset(), (0..100000).collect::<BitSet<u64>>());
+}
@@ -1,4 +1,4 @@
-use bittyset::{BitSet, bitset};
+use bittyset::{BitSet, BitSetRef, BitSetMut, bitset};
 use pretty_assertions::{assert_eq, assert_ne};
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
@@ -404,3 +404,329 @@ fn is_proper_subset_vec_prop(tv: helper::TwoVec) -> bool {
   set1.len() == set2.len() ||
     set1.is_proper_subset(&set2) && !set2.is_proper_subset(&set1)
 }
+
+fn naive_runs(set: &BitSet<u8>, within: std::ops::Range<usize>, present: bool)
+  -> Vec<std::ops::Range<usize>>
+{
+  let mut runs: Vec<std::ops::Range<usize>> = vec![];
+  for x in within {
+    if set.contains(x) == present {
+      match runs.last_mut() {
+        Some(r) if r.end == x => r.end = x + 1,
+        _ => runs.push(x..x + 1),
+      }
+    }
+  }
+  runs
+}
+
+#[quickcheck]
+fn runs_prop(vec: Vec<u8>) -> bool {
+  let set = vec.into_iter().map(|x| x as usize).collect::<BitSet<u8>>();
+  let runs = naive_runs(&set, 0..256, true);
+  let mut rev = runs.clone();
+  rev.reverse();
+
+  set.runs().collect::<Vec<_>>() == runs &&
+    set.runs().rev().collect::<Vec<_>>() == rev
+}
+
+#[quickcheck]
+fn runs_of_zeros_prop(vec: Vec<u8>, start: u16, len: u16) -> bool {
+  let set = vec.into_iter().map(|x| x as usize).collect::<BitSet<u8>>();
+  let within = start as usize..start as usize + len as usize;
+  let runs = naive_runs(&set, within.clone(), false);
+  let mut rev = runs.clone();
+  rev.reverse();
+
+  set.runs_of_zeros(within.clone()).collect::<Vec<_>>() == runs &&
+    set.runs_of_zeros(within).rev().collect::<Vec<_>>() == rev
+}
+
+#[test]
+fn runs_of_zeros_unbounded() {
+  let set = bitset![3, 4, 100];
+
+  assert_eq!(
+    set.runs_of_zeros(0..usize::MAX).collect::<Vec<_>>(),
+    vec![0..3, 5..100, 101..usize::MAX]);
+}
+
+#[quickcheck]
+fn from_ranges_prop(ranges: Vec<(u8, u8)>) -> bool {
+  let ranges = ranges.into_iter()
+    .map(|(a, b)| a as usize..b as usize)
+    .collect::<Vec<_>>();
+  let set1 = BitSet::<u32>::from_ranges(ranges.clone());
+  let set2 = ranges.into_iter().flatten().collect::<BitSet<u32>>();
+
+  set1 == set2 && BitSet::<u32>::from_ranges(set1.runs()) == set1
+}
+
+#[quickcheck]
+fn remove_ranges_prop(vec: Vec<u8>, ranges: Vec<(u8, u8)>) -> bool {
+  let ranges = ranges.into_iter()
+    .map(|(a, b)| a as usize..b as usize)
+    .collect::<Vec<_>>();
+  let mut set1 = vec.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let set2 = &set1 - &BitSet::from_ranges(ranges.clone());
+  set1.remove_ranges(ranges);
+
+  set1 == set2 && set1.as_blocks().len() == set2.into_blocks().len()
+}
+
+#[quickcheck]
+fn view_ops_prop(vec1: Vec<u16>, vec2: Vec<u16>, pad: u8) -> bool {
+  let set1 = vec1.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let set2 = vec2.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  // Trailing zero blocks must not affect the results.
+  let mut blocks = set2.as_view().as_slice().to_vec();
+  blocks.resize(blocks.len() + pad as usize % 4, 0);
+  let view1 = set1.as_view();
+  let view2 = BitSetRef::new(&blocks);
+
+  view1 | view2 == &set1 | &set2 &&
+    view1 & view2 == &set1 & &set2 &&
+    view1 - view2 == &set1 - &set2 &&
+    view1 ^ view2 == &set1 ^ &set2 &&
+    view1.is_subset(&view2) == set1.is_subset(&set2) &&
+    view2.len() == set2.len() &&
+    view2.to_bitset() == set2 &&
+    view2.iter().eq(set2.iter())
+}
+
+#[test]
+fn view_from_bytes() {
+  let words = [0x8001u16, 0x4];
+  let bytes = unsafe {
+    std::slice::from_raw_parts(words.as_ptr() as *const u8, 4)
+  };
+
+  let view = BitSetRef::<u16>::from_bytes(bytes).unwrap();
+  assert_eq!(view.iter().collect::<Vec<_>>(), vec![0, 15, 18]);
+
+  assert!(BitSetRef::<u16>::from_bytes(&bytes[1..3]).is_none());
+  assert!(BitSetRef::<u16>::from_bytes(&bytes[..3]).is_none());
+}
+
+#[test]
+fn view_mut() {
+  let mut blocks = [0u64; 2];
+  let mut view = BitSetMut::new(&mut blocks);
+
+  assert!(view.insert(3));
+  assert!(view.insert(100));
+  assert!(!view.insert(3));
+  assert!(view.contains(100));
+  assert!(!view.contains(1000));
+  assert!(view.remove(3));
+  assert!(!view.remove(1000));
+  assert_eq!(view.len(), 1);
+  assert_eq!(view.to_bitset().iter().collect::<Vec<_>>(), vec![100]);
+  assert_eq!(blocks, [0, 1 << 36]);
+}
+
+#[test]
+#[should_panic]
+fn view_mut_insert_out_of_bounds() {
+  let mut blocks = [0u8; 2];
+  BitSetMut::new(&mut blocks).insert(16);
+}
+
+#[quickcheck]
+fn blocks_prop(blocks: Vec<u16>) -> bool {
+  let set = BitSet::from_blocks(blocks.clone()).unwrap();
+  let expected = blocks.iter().enumerate()
+    .flat_map(|(i, &blk)| {
+      (0..16).filter(move |b| blk & (1 << b) != 0).map(move |b| i * 16 + b)
+    })
+    .collect::<Vec<_>>();
+  let nonzero = blocks.iter().enumerate()
+    .filter(|(_, &blk)| blk != 0)
+    .map(|(i, &blk)| (i * 16, blk))
+    .collect::<Vec<_>>();
+
+  set.iter().collect::<Vec<_>>() == expected &&
+    set.iter_blocks().collect::<Vec<_>>() == nonzero &&
+    set.as_blocks().last() != Some(&0) &&
+    BitSet::from_blocks(set.clone().into_blocks()) == Some(set)
+}
+
+#[quickcheck]
+fn op_len_prop(vec1: Vec<u16>, vec2: Vec<u16>) -> bool {
+  let set1 = vec1.into_iter().map(|x| x as usize).collect::<BitSet<u64>>();
+  let set2 = vec2.into_iter().map(|x| x as usize).collect::<BitSet<u64>>();
+
+  set1.union_len(&set2) == (&set1 | &set2).len() &&
+    set1.intersection_len(&set2) == (&set1 & &set2).len() &&
+    set1.difference_len(&set2) == (&set1 - &set2).len() &&
+    set2.difference_len(&set1) == (&set2 - &set1).len() &&
+    set1.symmetric_difference_len(&set2) == (&set1 ^ &set2).len()
+}
+
+#[quickcheck]
+fn array_block_prop(vec1: Vec<u16>, vec2: Vec<u16>) -> bool {
+  let vec1 = vec1.into_iter().map(|x| x as usize).collect::<Vec<_>>();
+  let vec2 = vec2.into_iter().map(|x| x as usize).collect::<Vec<_>>();
+  let set1 = vec1.iter().cloned().collect::<BitSet<[u64; 4]>>();
+  let set2 = vec2.iter().cloned().collect::<BitSet<[u64; 4]>>();
+  let set3 = vec1.iter().cloned().collect::<BitSet<[u16; 3]>>();
+  let set4 = vec2.iter().cloned().collect::<BitSet<[u16; 3]>>();
+  let expected1 = vec1.into_iter().collect::<BitSet<u8>>();
+  let expected2 = vec2.into_iter().collect::<BitSet<u8>>();
+
+  let same = |set: BitSet<[u64; 4]>, other: BitSet<[u16; 3]>, expected: BitSet<u8>| {
+    set.iter().eq(expected.iter()) &&
+      other.iter().eq(expected.iter()) &&
+      set.len() == expected.len() &&
+      set.runs().eq(expected.runs())
+  };
+
+  same(&set1 | &set2, &set3 | &set4, &expected1 | &expected2) &&
+    same(&set1 & &set2, &set3 & &set4, &expected1 & &expected2) &&
+    same(&set1 - &set2, &set3 - &set4, &expected1 - &expected2) &&
+    same(&set1 ^ &set2, &set3 ^ &set4, &expected1 ^ &expected2) &&
+    set1.is_subset(&set2) == expected1.is_subset(&expected2) &&
+    set3.is_proper_subset(&set4) == expected1.is_proper_subset(&expected2)
+}
+
+#[quickcheck]
+fn into_prop(vec1: Vec<u16>, vec2: Vec<u16>, vec3: Vec<u16>) -> bool {
+  let set1 = vec1.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let set2 = vec2.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let mut out = vec3.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  out.reserve(1 << 16);
+  let capacity = out.capacity();
+
+  let mut ok = true;
+  BitSet::union_into(&set1, &set2, &mut out);
+  ok &= out == &set1 | &set2;
+  BitSet::intersection_into(&set1, &set2, &mut out);
+  ok &= out == &set1 & &set2;
+  BitSet::difference_into(&set1, &set2, &mut out);
+  ok &= out == &set1 - &set2;
+  BitSet::symmetric_difference_into(&set1, &set2, &mut out);
+  ok &= out == &set1 ^ &set2;
+  out.assign_from(&set2);
+  ok &= out == set2;
+
+  ok && out.capacity() == capacity
+}
+
+#[quickcheck]
+fn fold_all_prop(vecs: Vec<Vec<u16>>, base: Vec<u16>) -> bool {
+  let sets = vecs.into_iter()
+    .map(|vec| vec.into_iter().map(|x| x as usize).collect::<BitSet<u32>>())
+    .collect::<Vec<_>>();
+  let base = base.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+
+  let union = sets.iter().fold(BitSet::new(), |acc, set| &acc | set);
+  let intersection = match sets.split_first() {
+    Some((first, rest)) => rest.iter().fold(first.clone(), |acc, set| &acc & set),
+    None => BitSet::new(),
+  };
+  let difference = sets.iter().fold(base.clone(), |acc, set| &acc - set);
+
+  BitSet::union_all(&sets) == union &&
+    BitSet::intersection_all(&sets) == intersection &&
+    BitSet::difference_of(&base, &sets) == difference &&
+    sets.iter().sum::<BitSet<u32>>() == union &&
+    sets.iter().product::<BitSet<u32>>() == intersection &&
+    sets.into_iter().collect::<BitSet<u32>>() == union
+}
+
+#[test]
+fn fold_all_large() {
+  let sets = (2..40).map(|n| (0..200000).step_by(n).collect::<BitSet>()).collect::<Vec<_>>();
+
+  assert_eq!(BitSet::union_all(&sets), (0..200000).filter(|x| (2..40).any(|n| x % n == 0)).collect());
+  assert_eq!(sets.iter().product::<BitSet>(), (0..200000).filter(|x| (2..40).all(|n| x % n == 0)).collect());
+}
+
+#[quickcheck]
+fn count_prop(vecs: Vec<Vec<u8>>, k: u8) -> bool {
+  let sets = vecs.into_iter()
+    .map(|vec| vec.into_iter().map(|x| x as usize).collect::<BitSet<u16>>())
+    .collect::<Vec<_>>();
+  let k = k as usize % (sets.len() + 2);
+  let num_bits = sets.iter().filter_map(|set| set.iter().last()).map(|x| x + 1).max().unwrap_or(0);
+  let counts = (0..num_bits)
+    .map(|x| sets.iter().filter(|set| set.contains(x)).count() as u32)
+    .collect::<Vec<_>>();
+
+  BitSet::column_counts(&sets) == counts &&
+    BitSet::at_least(k, &sets) == (0..num_bits).filter(|&x| counts[x] as usize >= k).collect() &&
+    BitSet::exactly(k, &sets) == (0..num_bits).filter(|&x| counts[x] as usize == k).collect()
+}
+
+#[quickcheck]
+fn expr_prop(a: Vec<u16>, b: Vec<u16>, c: Vec<u16>, d: Vec<u8>) -> bool {
+  let a = a.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let b = b.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let c = c.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let d = d.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+
+  let exprs = vec![
+    ((a.expr() | &b) & (c.expr() - &d), &(&a | &b) & &(&c - &d)),
+    (a.expr() ^ (b.expr() & (c.expr() | &d)), &a ^ &(&b & &(&c | &d))),
+    ((a.expr() - &b) | (c.expr() ^ (d.expr() & &a)), &(&a - &b) | &(&c ^ &(&d & &a))),
+    (d.expr() & &a & &b & &c, &(&(&d & &a) & &b) & &c),
+  ];
+
+  exprs.into_iter().all(|(expr, expected)| {
+    expr.evaluate() == expected &&
+      expr.iter().eq(expected.iter()) &&
+      expr.len() == expected.len() &&
+      expr.is_empty() == expected.is_empty() &&
+      expr.any() != expected.is_empty()
+  })
+}
+
+#[test]
+fn expr_large() {
+  let a = (0..1000000).step_by(3).collect::<BitSet>();
+  let b = (0..1000000).step_by(5).collect::<BitSet>();
+  let c = (0..500000).step_by(7).collect::<BitSet>();
+  let expr = (a.expr() ^ &b) - (c.expr() | &b);
+
+  assert_eq!(expr.evaluate(), &(&a ^ &b) - &(&c | &b));
+  assert_eq!(expr.iter().collect::<BitSet>(), expr.evaluate());
+  assert_eq!((c.expr() & &a & &b).len(), (0..500000).step_by(105).count());
+}
+
+#[quickcheck]
+fn trailing_zeros_prop(vec1: Vec<u16>, vec2: Vec<u16>, vec3: Vec<u16>) -> bool {
+  let set1 = vec1.into_iter().map(|x| x as usize).collect::<BitSet<u8>>();
+  let set2 = vec2.into_iter().map(|x| x as usize).collect::<BitSet<u8>>();
+  let set3 = vec3.into_iter().map(|x| x as usize).collect::<BitSet<u8>>();
+  let expected = |set: &BitSet<u8>| set.iter().collect::<BitSet<u8>>();
+
+  let sets = [&set1 & &set2, &set1 - &set2, &set1 ^ &set2, &(&set1 | &set3) - &set3];
+  sets.iter().all(|set| {
+    let other = expected(set);
+    *set == other &&
+      my_hash(set) == my_hash(&other) &&
+      set.as_blocks() == other.as_blocks() &&
+      set.is_subset(&other) && other.is_subset(set) &&
+      set.is_subset(&set1) == other.is_subset(&set1) &&
+      set2.is_subset(set) == set2.is_subset(&other)
+  })
+}
+
+#[test]
+fn block_ops_with_std_ops() {
+  use bittyset::BitBlock;
+  use std::ops::{BitAnd, Not};
+
+  // The methods of `BitBlock` do not clash with those of `std::ops`.
+  fn and_not<T>(x: T, y: T) -> T
+  where
+    T: BitBlock + BitAnd<Output = T> + Not<Output = T>,
+  {
+    assert!(x.bit_and(y.bit_not()) == x.bitand(y.not()));
+    x & !y
+  }
+
+  assert_eq!(and_not(0b1100u8, 0b1010), 0b0100);
+  assert_eq!(and_not(u64::MAX, 1), u64::MAX - 1);
+}
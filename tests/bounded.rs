@@ -0,0 +1,62 @@
+use bittyset::{BitSet, BoundedBitSet, OutOfRange};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[test]
+fn insert_out_of_range() {
+  let mut set = BoundedBitSet::<u8>::new(10);
+
+  assert_eq!(set.insert(9), Ok(true));
+  assert_eq!(set.insert(9), Ok(false));
+  assert_eq!(set.insert(10), Err(OutOfRange { value: 10, universe: 10 }));
+  assert!(!set.remove(10));
+  assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn full() {
+  let set = BoundedBitSet::<u8>::full(13);
+
+  assert!(set.is_full());
+  assert_eq!(set.len(), 13);
+  assert!((!&set).is_empty());
+  assert_eq!(set.iter_absent().count(), 0);
+  assert!(BoundedBitSet::<u8>::full(0).is_full());
+  assert_eq!(!BoundedBitSet::<u64>::new(64), BoundedBitSet::full(64));
+}
+
+#[test]
+#[should_panic(expected = "universes of the sets differ")]
+fn universe_mismatch() {
+  let _ = &BoundedBitSet::<u8>::new(10) | &BoundedBitSet::new(11);
+}
+
+#[test]
+fn from_bitset() {
+  let set = [1, 9].iter().cloned().collect::<BitSet<u8>>();
+
+  assert!(BoundedBitSet::from_bitset(set.clone(), 9).is_none());
+  assert_eq!(BoundedBitSet::from_bitset(set, 10).unwrap().iter_absent().count(), 8);
+}
+
+#[quickcheck]
+fn complement_prop(vec1: Vec<u8>, vec2: Vec<u8>, universe: u8) -> bool {
+  let universe = universe as usize + 1;
+  let mut set1 = BoundedBitSet::<u16>::new(universe);
+  let mut set2 = BoundedBitSet::<u16>::new(universe);
+  for x in vec1 {
+    let _ = set1.insert(x as usize);
+  }
+  for x in vec2 {
+    let _ = set2.insert(x as usize);
+  }
+
+  let absent = (0..universe).filter(|&x| !set1.contains(x)).collect::<Vec<_>>();
+
+  set1.iter_absent().collect::<Vec<_>>() == absent &&
+    (!&set1).iter().collect::<Vec<_>>() == absent &&
+    !!set1.clone() == set1 &&
+    !(&set1 | &set2) == &!&set1 & &!&set2 &&
+    &set1 - &set2 == &set1 & &!&set2 &&
+    set1.len() + (!&set1).len() == universe
+}
@@ -0,0 +1,67 @@
+use bittyset::{BitSet, CofiniteBitSet};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+fn make(vec: Vec<u8>, complemented: bool) -> CofiniteBitSet<u16> {
+  let set = vec.into_iter().map(|x| x as usize).collect::<BitSet<u16>>();
+  if complemented {
+    CofiniteBitSet::cofinite(set)
+  } else {
+    CofiniteBitSet::from_bitset(set)
+  }
+}
+
+/// Checks the membership of the values which may be present in either set.
+fn same(set: &CofiniteBitSet<u16>, f: impl Fn(usize) -> bool) -> bool {
+  (0..300).all(|x| set.contains(x) == f(x))
+}
+
+#[test]
+fn full_and_empty() {
+  let full = CofiniteBitSet::<u16>::full();
+  let empty = CofiniteBitSet::<u16>::new();
+
+  assert!(full.is_full());
+  assert!(empty.is_empty());
+  assert_eq!(!&full, empty);
+  assert_eq!(full.len(), None);
+  assert_eq!(empty.len(), Some(0));
+  assert!(full.iter().is_none());
+  assert!(empty.is_subset(&full));
+  assert!(!full.is_subset(&empty));
+}
+
+#[test]
+fn insert_remove() {
+  let mut set = CofiniteBitSet::<u16>::full();
+
+  assert!(set.remove(7));
+  assert!(!set.remove(7));
+  assert!(!set.contains(7));
+  assert!(set.insert(7));
+  assert!(!set.insert(7));
+  assert!(set.is_full());
+}
+
+#[quickcheck]
+fn ops_prop(vec1: Vec<u8>, co1: bool, vec2: Vec<u8>, co2: bool) -> bool {
+  let set1 = make(vec1, co1);
+  let set2 = make(vec2, co2);
+  let (a, b) = (|x| set1.contains(x), |x| set2.contains(x));
+
+  let mut set3 = set1.clone();
+  set3 -= &set2;
+  let subset = (0..300).all(|x| !a(x) || b(x)) && (!co1 || co2);
+
+  same(&(&set1 | &set2), |x| a(x) || b(x)) &&
+    same(&(&set1 & &set2), |x| a(x) && b(x)) &&
+    same(&set3, |x| a(x) && !b(x)) &&
+    same(&(set1.clone() ^ set2.clone()), |x| a(x) != b(x)) &&
+    same(&!&set1, |x| !a(x)) &&
+    (&set1 | &set2).is_cofinite() == (co1 || co2) &&
+    (&set1 & &set2).is_cofinite() == (co1 && co2) &&
+    set1.is_subset(&set2) == subset &&
+    (set1 == set2) == (co1 == co2 && (0..300).all(|x| a(x) == b(x))) &&
+    set1.iter().map(|iter| iter.collect::<Vec<_>>()) ==
+      set1.as_finite().map(|set| (0..300).filter(|&x| set.contains(x)).collect())
+}
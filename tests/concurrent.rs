@@ -0,0 +1,58 @@
+use bittyset::{BitSet, ConcurrentBitSet};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use std::thread;
+
+#[test]
+fn insert_remove() {
+  let set = ConcurrentBitSet::<u32>::new();
+
+  assert!(set.is_empty());
+  assert!(!set.contains(7));
+  assert!(!set.remove(7));
+  assert!(set.insert(7));
+  assert!(!set.insert(7));
+  assert!(set.insert(1 << 20));
+  assert!(set.contains(7));
+  assert!(set.contains(1 << 20));
+  assert!(!set.contains(1 << 19));
+  assert!(set.remove(7));
+  assert!(!set.remove(7));
+  assert_eq!(set.len(), 1);
+  assert_eq!(format!("{:?}", set), format!("{{{}}}", 1 << 20));
+}
+
+#[quickcheck]
+fn snapshot_prop(vec1: Vec<u16>, vec2: Vec<u16>) -> bool {
+  let set1 = vec1.iter().map(|&x| (x as usize) << 4).collect::<BitSet<u8>>();
+  let set2 = vec2.iter().map(|&x| x as usize).collect::<BitSet<u8>>();
+  let set = ConcurrentBitSet::<u8>::new();
+  for &x in &vec1 {
+    set.insert((x as usize) << 4);
+  }
+  set.union_from(&set2);
+
+  set.snapshot() == &set1 | &set2 && set.len() == (&set1 | &set2).len()
+}
+
+#[test]
+fn concurrent_insert() {
+  let set = ConcurrentBitSet::<u64>::new();
+  let fresh = thread::scope(|s| {
+    let handles = (0..8)
+      .map(|t| {
+        let set = &set;
+        s.spawn(move || {
+          (0..100000).step_by(t + 1).filter(|&i| set.insert(i)).count()
+        })
+      })
+      .collect::<Vec<_>>();
+    handles.into_iter().map(|h| h.join().unwrap()).sum::<usize>()
+  });
+
+  let expected = (0..100000)
+    .filter(|i| (1..=8).any(|t| i % t == 0))
+    .collect::<BitSet<u64>>();
+  assert_eq!(fresh, expected.len());
+  assert_eq!(set.snapshot(), expected);
+}
@@ -0,0 +1,89 @@
+use bittyset::{BitSet, CountingBitSet};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[test]
+fn widths() {
+  let mut counts = <CountingBitSet<u8>>::new(3);
+  // Two counters per block, with two bits unused.
+  for value in 0..6 {
+    counts.set_count(value, value + 1);
+  }
+
+  assert_eq!(counts.iter().collect::<Vec<_>>(), vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6)]);
+  assert_eq!(counts.max_count(), 7);
+
+  let mut wide = <CountingBitSet<u8>>::new(8);
+  wide.set_count(3, 255);
+  assert_eq!(wide.count(3), 255);
+  assert_eq!(wide.increment(3), None);
+}
+
+#[test]
+fn array_blocks() {
+  // The counters straddle the elements of the blocks.
+  let mut counts = <CountingBitSet<[u8; 4]>>::new(12);
+  counts.set_count(0, 0xabc);
+  counts.set_count(1, 0xfff);
+  counts.set_count(3, 0x123);
+
+  assert_eq!(counts.iter().collect::<Vec<_>>(), vec![(0, 0xabc), (1, 0xfff), (3, 0x123)]);
+  assert_eq!(counts.decrement(1), Some(0xffe));
+  assert_eq!(counts.sum(&counts).count(0), 0xfff);
+}
+
+#[test]
+#[should_panic(expected = "invalid counter width")]
+fn too_wide() {
+  let _ = <CountingBitSet<u8>>::new(9);
+}
+
+#[test]
+#[should_panic(expected = "counter widths of the sets differ")]
+fn width_mismatch() {
+  let _ = <CountingBitSet>::new(2).sum(&CountingBitSet::new(3));
+}
+
+fn make(bits: usize, ops: &[(u8, bool)]) -> (CountingBitSet<u16>, Vec<usize>) {
+  let mut counts = CountingBitSet::new(bits);
+  let mut model = vec![0; 256];
+  for &(value, inc) in ops {
+    let value = value as usize;
+    if inc {
+      let expected = if model[value] < counts.max_count() { Some(model[value] + 1) } else { None };
+      assert_eq!(counts.increment(value), expected);
+      model[value] = expected.unwrap_or(model[value]);
+    } else {
+      let expected = model[value].checked_sub(1);
+      assert_eq!(counts.decrement(value), expected);
+      model[value] = expected.unwrap_or(0);
+    }
+  }
+  (counts, model)
+}
+
+#[quickcheck]
+fn counting_prop(bits: u8, ops1: Vec<(u8, bool)>, ops2: Vec<(u8, bool)>) -> bool {
+  let bits = bits as usize % 5 + 1;
+  let (counts1, model1) = make(bits, &ops1);
+  let (counts2, model2) = make(bits, &ops2);
+  let max_count = counts1.max_count();
+
+  let check = |counts: &CountingBitSet<u16>, f: &dyn Fn(usize, usize) -> usize| {
+    let expected = (0..256)
+      .map(|x| (x, f(model1[x], model2[x])))
+      .filter(|&(_, count)| count > 0)
+      .collect::<Vec<_>>();
+    counts.iter().collect::<Vec<_>>() == expected
+  };
+
+  let support = (0..256).filter(|&x| model1[x] > 0).collect::<BitSet<u16>>();
+
+  (0..256).all(|x| counts1.count(x) == model1[x]) &&
+    counts1.support() == support &&
+    check(&counts1.min(&counts2), &usize::min) &&
+    check(&counts1.max(&counts2), &usize::max) &&
+    check(&counts1.sum(&counts2), &|x, y| (x + y).min(max_count)) &&
+    (counts1 == counts2) == (model1 == model2) &&
+    counts1.is_empty() == support.is_empty()
+}
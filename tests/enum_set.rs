@@ -0,0 +1,81 @@
+use bittyset::{BitSetKey, EnumSet, enum_set};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BitSetKey)]
+enum Permission {
+  Read,
+  Write,
+  Exec,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BitSetKey)]
+enum Empty {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BitSetKey)]
+enum Wide {
+  V0, V1, V2, V3, V4, V5, V6, V7, V8, V9, V10, V11, V12, V13, V14, V15, V16,
+  V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30, V31,
+  V32, V33, V34, V35, V36, V37, V38, V39, V40, V41, V42, V43, V44, V45, V46,
+  V47, V48, V49, V50, V51, V52, V53, V54, V55, V56, V57, V58, V59, V60, V61,
+  V62, V63, V64, V65, V66, V67, V68, V69, V70, V71, V72, V73, V74, V75, V76,
+  V77, V78, V79, V80, V81, V82, V83, V84, V85, V86, V87, V88, V89, V90, V91,
+  V92, V93, V94, V95, V96, V97, V98, V99, V100, V101, V102, V103, V104, V105,
+  V106, V107, V108, V109, V110, V111, V112, V113, V114, V115, V116, V117,
+  V118, V119, V120, V121, V122, V123, V124, V125, V126, V127, V128, V129,
+  V130, V131, V132, V133, V134, V135, V136, V137, V138, V139, V140, V141,
+  V142, V143, V144, V145, V146, V147, V148, V149
+}
+
+const READ_WRITE: EnumSet<Permission> = enum_set![Permission::Read, Permission::Write];
+const WIDE: EnumSet<Wide> = enum_set![Wide::V3, Wide::V70, Wide::V149];
+
+#[test]
+fn block_types() {
+  assert_eq!(READ_WRITE.as_block(), 0b011u8);
+  assert_eq!(EnumSet::<Permission>::all().as_block(), 0b111u8);
+  assert_eq!(EnumSet::<Empty>::all().as_block(), 0u8);
+  assert_eq!(<Wide as BitSetKey>::COUNT, 150);
+  assert_eq!(EnumSet::<Wide>::all().as_block(), [u64::MAX, u64::MAX, u64::MAX >> 42]);
+  assert_eq!(WIDE.as_block(), [1 << 3, 1 << 6, 1 << 21]);
+}
+
+#[test]
+fn ops() {
+  let exec = EnumSet::from(Permission::Exec);
+
+  assert_eq!(READ_WRITE | exec, EnumSet::all());
+  assert_eq!(READ_WRITE | Permission::Exec, EnumSet::all());
+  assert_eq!(READ_WRITE & Permission::Write, enum_set![Permission::Write]);
+  assert_eq!(READ_WRITE - Permission::Write, enum_set![Permission::Read]);
+  assert_eq!(READ_WRITE ^ EnumSet::all(), exec);
+  assert_eq!(!READ_WRITE, exec);
+  assert_eq!(!EnumSet::<Permission>::all(), EnumSet::empty());
+  assert!(exec.is_disjoint(&READ_WRITE));
+  assert!(READ_WRITE.is_subset(&EnumSet::all()));
+  assert!(EnumSet::<Permission>::all().is_superset(&READ_WRITE));
+  assert_eq!(READ_WRITE.iter().rev().collect::<Vec<_>>(), vec![Permission::Write, Permission::Read]);
+  assert_eq!(format!("{:?}", !WIDE - Wide::V0 - Wide::V1 - Wide::V2), format!("{:?}", !WIDE - enum_set![Wide::V0, Wide::V1, Wide::V2]));
+  assert_eq!(WIDE.iter().collect::<Vec<_>>(), vec![Wide::V3, Wide::V70, Wide::V149]);
+  assert_eq!((!WIDE).len(), 147);
+  assert_eq!(WIDE.to_bitset().iter().collect::<Vec<_>>(), vec![3, 70, 149]);
+}
+
+#[quickcheck]
+fn ops_prop(vec1: Vec<u8>, vec2: Vec<u8>) -> bool {
+  let set1 = vec1.iter().map(|&x| Wide::from_index(x as usize % 150)).collect::<EnumSet<Wide>>();
+  let set2 = vec2.iter().map(|&x| Wide::from_index(x as usize % 150)).collect::<EnumSet<Wide>>();
+  let bits = |set: EnumSet<Wide>| set.iter().map(Wide::to_index).collect::<Vec<_>>();
+  let expected = |f: fn(bool, bool) -> bool| {
+    (0..150).filter(|&i| f(set1.contains(Wide::from_index(i)), set2.contains(Wide::from_index(i))))
+      .collect::<Vec<_>>()
+  };
+
+  bits(set1 | set2) == expected(|x, y| x || y) &&
+    bits(set1 & set2) == expected(|x, y| x && y) &&
+    bits(set1 - set2) == expected(|x, y| x && !y) &&
+    bits(set1 ^ set2) == expected(|x, y| x != y) &&
+    bits(!set1) == expected(|x, _| !x) &&
+    set1.len() + (!set1).len() == 150 &&
+    set1.iter().rev().eq(bits(set1).into_iter().rev().map(Wide::from_index))
+}
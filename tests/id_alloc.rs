@@ -0,0 +1,64 @@
+use std::collections::BTreeSet;
+use bittyset::IdAllocator;
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[test]
+fn many_levels() {
+  let mut ids = <IdAllocator<u8>>::new();
+  for id in 0..5000 {
+    assert_eq!(ids.alloc(), id);
+  }
+  assert!(ids.free(4321));
+  assert!(ids.free(17));
+  assert_eq!(ids.alloc(), 17);
+  assert_eq!(ids.alloc(), 4321);
+  assert_eq!(ids.alloc(), 5000);
+  assert_eq!(ids.len(), 5001);
+}
+
+#[test]
+fn alloc_past_full_prefix() {
+  let mut ids = <IdAllocator<u8>>::new();
+  ids.alloc_range(0..64);
+  ids.alloc_at(64);
+  ids.alloc_at(70);
+
+  assert_eq!(ids.alloc(), 65);
+  ids.alloc_range(66..70);
+  assert_eq!(ids.alloc(), 71);
+}
+
+/// Compares the allocator against a set of the allocated ids.
+#[quickcheck]
+fn alloc_prop(ops: Vec<(u8, u16, u8)>) -> bool {
+  let mut ids = <IdAllocator<u8>>::new();
+  let mut model = BTreeSet::new();
+
+  ops.into_iter().all(|(op, id, n)| {
+    let id = id as usize % 2000;
+    let ok = match op % 6 {
+      0 => {
+        let expected = (0..).find(|x| !model.contains(x)).unwrap();
+        model.insert(expected);
+        ids.alloc() == expected
+      },
+      1 => ids.alloc_at(id) == model.insert(id),
+      2 => {
+        ids.alloc_range(id..id + n as usize);
+        model.extend(id..id + n as usize);
+        true
+      },
+      3 => {
+        ids.clear();
+        model.clear();
+        true
+      },
+      _ => ids.free(id) == model.remove(&id),
+    };
+
+    ok && ids.len() == model.len() &&
+      ids.iter().collect::<Vec<_>>() == model.iter().cloned().collect::<Vec<_>>() &&
+      model.iter().all(|&x| ids.is_allocated(x))
+  })
+}
@@ -0,0 +1,113 @@
+#![cfg(unix)]
+
+use bittyset::{BitSet, MmapBitSet};
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+  std::env::temp_dir()
+    .join(format!("bittyset-test-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn create_and_open() {
+  let path = temp_path("create_and_open");
+  let set = (0..100000).step_by(7).collect::<BitSet<u32>>();
+
+  let mmap = MmapBitSet::create_from(&path, &set).unwrap();
+  assert_eq!(mmap.len(), set.len());
+  mmap.flush().unwrap();
+  drop(mmap);
+
+  let mmap = MmapBitSet::<u32>::open(&path).unwrap();
+  assert_eq!(mmap.to_bitset(), set);
+  assert!(mmap.contains(700));
+  assert!(!mmap.contains(701));
+  assert!(!mmap.contains(1 << 40));
+  assert!(mmap.is_subset(&set.as_view()));
+
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn open_read_only() {
+  use std::os::unix::fs::PermissionsExt;
+
+  let path = temp_path("open_read_only");
+  let set = (0..5000).step_by(3).collect::<BitSet<u64>>();
+  MmapBitSet::create_from(&path, &set).unwrap().flush().unwrap();
+  fs::set_permissions(&path, fs::Permissions::from_mode(0o444)).unwrap();
+
+  // Opening for writing fails unless the permissions are bypassed, as for
+  // root.
+  if let Err(err) = MmapBitSet::<u64>::open(&path) {
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+  }
+
+  let mmap = MmapBitSet::<u64>::open_read_only(&path).unwrap();
+  assert_eq!(mmap.to_bitset(), set);
+  assert_eq!(mmap.len(), set.len());
+  assert!(mmap.contains(4998));
+  assert!(!mmap.contains(4999));
+  assert!(mmap.is_subset(&set.as_view()));
+  assert_eq!(format!("{:?}", mmap), format!("{:?}", set));
+
+  let err = MmapBitSet::<u32>::open_read_only(&path).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn grow() {
+  let path = temp_path("grow");
+
+  let mut mmap = MmapBitSet::<u64>::create(&path).unwrap();
+  assert!(mmap.is_empty());
+  assert_eq!(mmap.insert(5).unwrap(), true);
+  assert_eq!(mmap.insert(5).unwrap(), false);
+  assert_eq!(mmap.insert(3_000_000).unwrap(), true);
+  assert_eq!(mmap.insert(40_000).unwrap(), true);
+  assert_eq!(mmap.remove(40_000), true);
+  assert_eq!(mmap.remove(1 << 40), false);
+  mmap.flush().unwrap();
+  drop(mmap);
+
+  let mut mmap = MmapBitSet::<u64>::open(&path).unwrap();
+  assert_eq!(mmap.iter().collect::<Vec<_>>(), vec![5, 3_000_000]);
+
+  mmap.clear();
+  mmap.flush().unwrap();
+  drop(mmap);
+
+  assert!(MmapBitSet::<u64>::open(&path).unwrap().is_empty());
+
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn invalid_header() {
+  let path = temp_path("invalid_header");
+
+  MmapBitSet::<u64>::create(&path).unwrap().insert(100).unwrap();
+
+  let err = MmapBitSet::<u32>::open(&path).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+  fs::OpenOptions::new().append(true).open(&path).unwrap()
+    .write_all(&[0]).unwrap();
+  let err = MmapBitSet::<u64>::open(&path).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+  fs::write(&path, b"not a bitset file, but long enough").unwrap();
+  let err = MmapBitSet::<u64>::open(&path).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+  fs::write(&path, b"short").unwrap();
+  let err = MmapBitSet::<u64>::open(&path).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+  fs::remove_file(&path).unwrap();
+}
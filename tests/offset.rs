@@ -0,0 +1,73 @@
+use std::collections::BTreeSet;
+use bittyset::OffsetBitSet;
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+const TIMESTAMP: i64 = 1_700_000_000_000;
+
+fn make(base: i8, vec: &[i16]) -> Vec<i64> {
+  let base = TIMESTAMP * (base % 2) as i64;
+  vec.iter().map(|&x| base + x as i64).collect()
+}
+
+#[test]
+fn extremes() {
+  let mut set = <OffsetBitSet<i64, u8>>::new();
+  set.insert(i64::MAX);
+  set.insert(i64::MAX - 100);
+  assert_eq!(set.iter().collect::<Vec<_>>(), vec![i64::MAX - 100, i64::MAX]);
+
+  let mut set = <OffsetBitSet<u64>>::new();
+  set.insert(u64::MAX);
+  set.insert(u64::MAX - 1000);
+  assert!(set.contains(u64::MAX));
+  assert!(!set.contains(0));
+  assert!(!set.remove(3));
+  assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn descending() {
+  let mut set = <OffsetBitSet<i64, u8>>::new();
+  for x in (-5000..5000).rev() {
+    set.insert(TIMESTAMP + x);
+  }
+
+  assert_eq!(set.len(), 10000);
+  assert_eq!(set.iter().next(), Some(TIMESTAMP - 5000));
+  assert!(set.as_bitset().as_blocks().len() < 2 * 10000 / 8 + 2);
+}
+
+#[quickcheck]
+fn ops_prop(base: i8, vec1: Vec<i16>, vec2: Vec<i16>) -> bool {
+  let (vec1, vec2) = (make(base, &vec1), make(base, &vec2));
+  let set1 = vec1.iter().cloned().collect::<OffsetBitSet<i64, u16>>();
+  let set2 = vec2.iter().cloned().collect::<OffsetBitSet<i64, u16>>();
+  let ref1 = vec1.iter().cloned().collect::<BTreeSet<_>>();
+  let ref2 = vec2.iter().cloned().collect::<BTreeSet<_>>();
+
+  let collect = |set: OffsetBitSet<i64, u16>| set.iter().collect::<Vec<_>>();
+  let mut set3 = set1.clone();
+  set3 -= &set2;
+
+  set1.iter().collect::<Vec<_>>() == ref1.iter().cloned().collect::<Vec<_>>() &&
+    collect(&set1 | &set2) == ref1.union(&ref2).cloned().collect::<Vec<_>>() &&
+    collect(&set1 & &set2) == ref1.intersection(&ref2).cloned().collect::<Vec<_>>() &&
+    collect(set3) == ref1.difference(&ref2).cloned().collect::<Vec<_>>() &&
+    collect(set1.clone() ^ set2.clone()) == ref1.symmetric_difference(&ref2).cloned().collect::<Vec<_>>() &&
+    set1.is_subset(&set2) == ref1.is_subset(&ref2) &&
+    (set1 == set2) == (ref1 == ref2) &&
+    vec1.iter().all(|&x| set1.contains(x))
+}
+
+#[quickcheck]
+fn eq_prop(vec: Vec<i16>) -> bool {
+  let set1 = vec.iter().map(|&x| x as i64).collect::<OffsetBitSet<i64, u8>>();
+  let mut set2 = vec.iter().rev().map(|&x| x as i64).collect::<OffsetBitSet<i64, u8>>();
+  set2.insert(-40000);
+  set2.remove(-40000);
+  set2.insert(40000);
+  set2.remove(40000);
+
+  set1 == set2 && (&set1 ^ &set2).is_empty()
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "rayon")]
+
+use bittyset::BitSet;
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use rayon::prelude::*;
+
+#[quickcheck]
+fn par_ops_prop(vec1: Vec<u32>, vec2: Vec<u32>) -> bool {
+  let vec1 = vec1.into_iter().map(|x| x as usize % 500000).collect::<Vec<_>>();
+  let vec2 = vec2.into_iter().map(|x| x as usize % 500000).collect::<Vec<_>>();
+  let set1 = vec1.iter().cloned().collect::<BitSet<u32>>();
+  let set2 = vec2.par_iter().cloned().collect::<BitSet<u32>>();
+  let mut set3 = set1.clone();
+  set3.par_extend(vec2.clone());
+
+  set2 == vec2.into_iter().collect::<BitSet<u32>>() &&
+    set3 == &set1 | &set2 &&
+    set1.par_union(&set2) == &set1 | &set2 &&
+    set1.par_intersection(&set2) == &set1 & &set2 &&
+    set1.par_difference(&set2) == &set1 - &set2 &&
+    set1.par_len() == set1.len() &&
+    set1.par_is_subset(&set3) &&
+    set1.par_is_subset(&set2) == set1.is_subset(&set2)
+}
+
+#[test]
+fn par_iter_large() {
+  let set = (0..10_000_000).step_by(7)
+    .chain(9_000_000..9_100_000)
+    .collect::<BitSet<u64>>();
+  let mut vec = set.par_iter().collect::<Vec<_>>();
+  vec.sort_unstable();
+
+  assert_eq!(vec, set.iter().collect::<Vec<_>>());
+  assert_eq!((&set).into_par_iter().count(), set.len());
+}
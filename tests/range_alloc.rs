@@ -0,0 +1,102 @@
+use std::ops::Range;
+use bittyset::{Fit, FreeSpaceStats, RangeAllocator};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[test]
+fn next_fit_wraps() {
+  let mut pages = <RangeAllocator<u8>>::new(30);
+
+  assert_eq!(pages.alloc(10, Fit::Next), Some(0..10));
+  assert_eq!(pages.alloc(10, Fit::Next), Some(10..20));
+  assert!(pages.free(0..10));
+  assert_eq!(pages.alloc(5, Fit::Next), Some(20..25));
+  assert_eq!(pages.alloc(8, Fit::Next), Some(0..8));
+  assert_eq!(pages.alloc(8, Fit::Next), None);
+}
+
+#[test]
+fn atomic_ranges() {
+  let mut pages = <RangeAllocator<u8>>::new(30);
+
+  assert!(pages.alloc_range(5..10));
+  assert!(!pages.alloc_range(8..12));
+  assert!(!pages.is_allocated(11));
+  assert!(!pages.alloc_range(25..31));
+  assert!(!pages.free(4..10));
+  assert!(pages.is_allocated(5));
+  assert!(pages.free(5..10));
+  assert_eq!(pages.stats(), FreeSpaceStats { free: 30, free_runs: 1, largest_free_run: 30 });
+  assert_eq!(pages.stats().fragmentation(), 0.0);
+}
+
+/// Returns the maximal runs of `false`s.
+fn free_runs(model: &[bool]) -> Vec<Range<usize>> {
+  let mut runs = Vec::new();
+  let mut i = 0;
+  while i < model.len() {
+    let start = i;
+    while i < model.len() && !model[i] {
+      i += 1;
+    }
+    if i > start {
+      runs.push(start..i);
+    }
+    i += 1;
+  }
+  runs
+}
+
+/// Checks the allocated ranges against a list of the bits allocated.
+#[quickcheck]
+fn alloc_prop(ops: Vec<(u8, u8, u8)>) -> bool {
+  let capacity = 200;
+  let mut pages = <RangeAllocator<u16>>::new(capacity);
+  let mut model = vec![false; capacity];
+  let mut ranges = Vec::<Range<usize>>::new();
+
+  ops.into_iter().all(|(op, len, index)| {
+    let len = len as usize % 20 + 1;
+    let runs = free_runs(&model);
+
+    let ok = match op % 4 {
+      3 => {
+        if !ranges.is_empty() {
+          let range = ranges.swap_remove(index as usize % ranges.len());
+          model[range.clone()].iter_mut().for_each(|x| *x = false);
+          pages.free(range)
+        } else {
+          true
+        }
+      },
+      op => {
+        let fit = [Fit::First, Fit::Best, Fit::Next][op as usize];
+        let expected = match fit {
+          Fit::First => runs.iter().find(|r| r.len() >= len),
+          Fit::Best => runs.iter().filter(|r| r.len() >= len).min_by_key(|r| r.len()),
+          Fit::Next => None,
+        }.map(|r| r.start..r.start + len);
+
+        let range = pages.alloc(len, fit);
+        let ok = match &range {
+          Some(range) if fit == Fit::Next => model[range.clone()].iter().all(|&x| !x),
+          None if fit == Fit::Next => runs.iter().all(|r| r.len() < len),
+          _ => range == expected,
+        };
+        if let Some(range) = range {
+          model[range.clone()].iter_mut().for_each(|x| *x = true);
+          ranges.push(range);
+        }
+        ok
+      },
+    };
+
+    let runs = free_runs(&model);
+    let expected = FreeSpaceStats {
+      free: runs.iter().map(|r| r.len()).sum(),
+      free_runs: runs.len(),
+      largest_free_run: runs.iter().map(|r| r.len()).max().unwrap_or(0),
+    };
+    ok && pages.stats() == expected && (0..capacity).all(|i| pages.is_allocated(i) == model[i])
+  })
+}
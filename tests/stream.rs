@@ -0,0 +1,81 @@
+use bittyset::{BitSet, BlockReader, BlockWriter, MergeBlocks};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use std::io::ErrorKind;
+
+fn serialize(vec: Vec<u16>) -> Vec<u8> {
+  let set = vec.into_iter().map(|x| x as usize).collect::<BitSet<u32>>();
+  let mut buf = vec![];
+  set.write_to(&mut buf).unwrap();
+  buf
+}
+
+fn readers(bufs: &[Vec<u8>]) -> Vec<BlockReader<&[u8], u32>> {
+  bufs.iter().map(|buf| BlockReader::new(&buf[..]).unwrap()).collect()
+}
+
+fn deserialize(buf: &[u8]) -> BitSet<u32> {
+  BitSet::read_from(buf).unwrap()
+}
+
+#[quickcheck]
+fn merge_prop(vec1: Vec<u16>, vec2: Vec<u16>, vec3: Vec<u16>) -> bool {
+  let bufs = vec![serialize(vec1), serialize(vec2), serialize(vec3)];
+  let sets = bufs.iter().map(|buf| deserialize(buf)).collect::<Vec<_>>();
+
+  let union = MergeBlocks::union(readers(&bufs)).write_to(vec![]).unwrap();
+  let intersection = MergeBlocks::intersection(readers(&bufs))
+    .write_to(vec![]).unwrap();
+  let mut rs = readers(&bufs);
+  let base = rs.remove(0);
+  let difference = MergeBlocks::difference(base, rs).write_to(vec![]).unwrap();
+  let symmetric_difference = MergeBlocks::symmetric_difference(readers(&bufs))
+    .write_to(vec![]).unwrap();
+  let elements = MergeBlocks::union(readers(&bufs)).elements()
+    .collect::<Result<Vec<_>, _>>().unwrap();
+
+  let expected_union = &(&sets[0] | &sets[1]) | &sets[2];
+
+  deserialize(&union) == expected_union &&
+    deserialize(&intersection) == &(&sets[0] & &sets[1]) & &sets[2] &&
+    deserialize(&difference) == &(&sets[0] - &sets[1]) - &sets[2] &&
+    deserialize(&symmetric_difference) == &(&sets[0] ^ &sets[1]) ^ &sets[2] &&
+    elements == expected_union.iter().collect::<Vec<_>>()
+}
+
+#[test]
+fn merge_no_inputs() {
+  let merged = MergeBlocks::<&[u8], u32>::intersection(vec![]);
+  assert_eq!(merged.num_blocks(), 0);
+  assert_eq!(merged.elements().count(), 0);
+}
+
+#[test]
+fn invalid_inputs() {
+  let buf = serialize(vec![1, 1000]);
+
+  let err = BlockReader::<_, u64>::new(&buf[..]).err().unwrap();
+  assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+  let err = BlockReader::<_, u32>::new(&b"BITTYSET"[..]).err().unwrap();
+  assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+  let truncated = &buf[..buf.len() - 1];
+  let err = MergeBlocks::union(vec![BlockReader::<_, u32>::new(truncated).unwrap()])
+    .elements()
+    .find_map(Result::err)
+    .unwrap();
+  assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn block_writer_count() {
+  let mut writer = BlockWriter::<_, u8>::new(vec![], 2).unwrap();
+  writer.write_block(1).unwrap();
+  assert_eq!(
+    writer.write_blocks(&[2, 3]).unwrap_err().kind(),
+    ErrorKind::InvalidInput);
+
+  let writer = BlockWriter::<_, u8>::new(vec![], 2).unwrap();
+  assert_eq!(writer.finish().unwrap_err().kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeSet;
+use bittyset::{BitSet, TrackedBitSet};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[test]
+fn remove_many() {
+  let mut set = <TrackedBitSet>::new();
+  set.extend(0..100_000);
+  for x in (0..100_000).filter(|x| x % 10 != 0) {
+    assert!(set.remove(x));
+  }
+
+  assert_eq!(set.len(), 10_000);
+  assert_eq!(set.iter_inserted().collect::<Vec<_>>(), (0..100_000).step_by(10).collect::<Vec<_>>());
+  for x in (0..100_000).step_by(10).rev() {
+    assert!(set.remove(x));
+  }
+  assert!(set.is_empty());
+  assert_eq!(set.iter_inserted().count(), 0);
+}
+
+#[test]
+fn reinsert() {
+  let mut set = <TrackedBitSet>::new();
+  set.extend(vec![5, 1, 9]);
+  set.remove(5);
+  set.insert(5);
+
+  assert_eq!(set.iter_inserted().collect::<Vec<_>>(), vec![1, 9, 5]);
+  assert_eq!(set.len(), 3);
+}
+
+/// Compares the set against an insertion ordered list, with clears in between.
+#[quickcheck]
+fn tracked_prop(ops: Vec<(u8, u16)>) -> bool {
+  let mut set = <TrackedBitSet<u16>>::new();
+  let mut order = Vec::new();
+
+  ops.into_iter().all(|(op, x)| {
+    let x = x as usize % 1000;
+    match op % 8 {
+      0 => {
+        set.clear();
+        order.clear();
+      },
+      1..=3 => {
+        let removed = set.remove(x);
+        let present = order.contains(&x);
+        order.retain(|&y| y != x);
+        if removed != present {
+          return false;
+        }
+      },
+      _ => {
+        let inserted = set.insert(x);
+        let absent = !order.contains(&x);
+        if absent {
+          order.push(x);
+        }
+        if inserted != absent {
+          return false;
+        }
+      },
+    }
+
+    let sorted = order.iter().cloned().collect::<BTreeSet<_>>();
+    set.iter_inserted().collect::<Vec<_>>() == order &&
+      set.iter().collect::<Vec<_>>() == sorted.iter().cloned().collect::<Vec<_>>() &&
+      set.len() == order.len() &&
+      *set.as_bitset() == sorted.iter().cloned().collect::<BitSet<u16>>()
+  })
+}
@@ -0,0 +1,53 @@
+use bittyset::{BitIndex, BitSet, TypedBitSet};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct NodeId(u32);
+
+impl BitIndex for NodeId {
+  fn to_usize(self) -> usize {
+    self.0 as usize
+  }
+
+  fn from_usize(index: usize) -> Self {
+    NodeId(index as u32)
+  }
+}
+
+#[test]
+fn insert_remove() {
+  let mut set = TypedBitSet::<NodeId, u8>::new();
+
+  assert!(set.insert(NodeId(7)));
+  assert!(set.insert(NodeId(300)));
+  assert!(!set.insert(NodeId(7)));
+  assert!(set.contains(NodeId(300)));
+  assert!(set.remove(NodeId(300)));
+  assert!(!set.contains(NodeId(300)));
+  assert_eq!(set.len(), 1);
+  assert_eq!(set.iter().collect::<Vec<_>>(), vec![NodeId(7)]);
+  assert_eq!(format!("{:?}", set), "{NodeId(7)}");
+  assert_eq!(set.into_bitset(), [7].iter().cloned().collect::<BitSet<u8>>());
+}
+
+#[quickcheck]
+fn ops_prop(vec1: Vec<u16>, vec2: Vec<u16>) -> bool {
+  let set1 = vec1.iter().map(|&x| NodeId(x as u32)).collect::<TypedBitSet<NodeId>>();
+  let set2 = vec2.iter().map(|&x| NodeId(x as u32)).collect::<TypedBitSet<NodeId>>();
+  let bitset1 = vec1.iter().map(|&x| x as usize).collect::<BitSet>();
+  let bitset2 = vec2.iter().map(|&x| x as usize).collect::<BitSet>();
+
+  let same = |set: TypedBitSet<NodeId>, expected: BitSet| {
+    set.iter().map(|x| x.0 as usize).eq(expected.iter()) && set.into_bitset() == expected
+  };
+
+  let mut set3 = set1.clone();
+  set3 ^= &set2;
+
+  same(&set1 | &set2, &bitset1 | &bitset2) &&
+    same(&set1 & &set2, &bitset1 & &bitset2) &&
+    same(&set1 - &set2, &bitset1 - &bitset2) &&
+    same(set3, &bitset1 ^ &bitset2) &&
+    set1.is_subset(&set2) == bitset1.is_subset(&bitset2)
+}
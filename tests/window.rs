@@ -0,0 +1,71 @@
+use std::collections::BTreeSet;
+use bittyset::{Mark, SlidingWindow, TooOld};
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+
+#[test]
+fn empty() {
+  let window = <SlidingWindow<u8>>::new(10);
+
+  assert_eq!(window.start(), 0);
+  assert_eq!(window.end(), 0);
+  assert_eq!(window.missing().count(), 0);
+  assert!(!window.contains(0));
+}
+
+#[test]
+fn jump() {
+  let mut window = <SlidingWindow<u8>>::new(20);
+  for seq in 0..20 {
+    window.mark(seq).unwrap();
+  }
+  window.advance_to(1_000_000);
+
+  assert_eq!(window.start(), 999_981);
+  assert_eq!(window.missing().count(), 20);
+  assert_eq!(window.mark(0), Err(TooOld { seq: 0, start: 999_981 }));
+  assert_eq!(window.mark(999_981), Ok(Mark::Fresh));
+}
+
+#[test]
+#[should_panic(expected = "window size must be positive")]
+fn zero_size() {
+  let _ = <SlidingWindow>::new(0);
+}
+
+/// Compares the window against a set of all the marked sequence numbers.
+#[quickcheck]
+fn window_prop(size: u8, ops: Vec<(bool, u16)>) -> bool {
+  let size = size as u64 + 1;
+  let mut window = <SlidingWindow<u16>>::new(size as usize);
+  let mut marked = BTreeSet::new();
+  let mut end = 0u64;
+
+  ops.into_iter().all(|(advance, delta)| {
+    // Keep most sequence numbers around the window.
+    let seq = (end + delta as u64 % 512).saturating_sub(256);
+    let start = end.saturating_sub(size);
+
+    let ok = if advance {
+      window.advance_to(seq);
+      true
+    } else {
+      let expected = if seq < start {
+        Err(TooOld { seq, start })
+      } else if marked.insert(seq) {
+        Ok(Mark::Fresh)
+      } else {
+        Ok(Mark::Duplicate)
+      };
+      window.mark(seq) == expected
+    };
+
+    end = end.max(seq + 1);
+    let start = end.saturating_sub(size);
+    let missing = (start..end).filter(|x| !marked.contains(x)).collect::<Vec<_>>();
+
+    ok && window.start() == start && window.end() == end &&
+      window.missing().collect::<Vec<_>>() == missing &&
+      (start..end).all(|x| window.contains(x) == marked.contains(&x))
+  })
+}